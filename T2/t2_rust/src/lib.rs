@@ -1,193 +1,88 @@
+use mancala_core::{GameSituation, IllegalMove, Move, OwareSituation, Player, RuleSet};
 use wasm_bindgen::prelude::wasm_bindgen;
 
-#[wasm_bindgen]
-pub fn mancala_result(flag: i32, seq: &[i32], size: i32) -> i32 {
-    let mut game_situation = GameSituation::new(flag);
-    for i in 0..size {
-        match game_situation.act(seq[i as usize]) {
-            ILLEGAL => {
-                return ILLEGAL + i;
-            }
-            _ => {}
-        }
-    }
-
-    if game_situation.ended {
-        if flag == 1 {
-            ENDED + game_situation.board[PLAYER_1_SCORE_HOLE] -
-                game_situation.board[PLAYER_2_SCORE_HOLE]
-        } else {
-            ENDED + game_situation.board[PLAYER_2_SCORE_HOLE] -
-                game_situation.board[PLAYER_1_SCORE_HOLE]
-        }
-    } else {
-        if flag == 1 {
-            NOT_ENDED + game_situation.board[PLAYER_1_SCORE_HOLE]
-        } else {
-            NOT_ENDED + game_situation.board[PLAYER_2_SCORE_HOLE]
-        }
-    }
-}
-
-const HOLE_NUMBER: usize = 14;
-
-pub struct GameSituation {
-    pub actor: i32,
-    board: [i32; HOLE_NUMBER],
-    ended: bool,
-}
-
-const PLAYER_1_SCORE_HOLE: usize = 6;
-const PLAYER_2_SCORE_HOLE: usize = 13;
-
-
 const ENDED: i32 = 15000;
 const NOT_ENDED: i32 = 20000;
 const ILLEGAL: i32 = 30000;
 
-impl GameSituation {
-    pub fn new(first_actor: i32) -> GameSituation {
-        let mut situation = GameSituation {
-            actor: first_actor,
-            board: [4; HOLE_NUMBER],
-            ended: false,
-        };
-
-        situation.board[PLAYER_1_SCORE_HOLE] = 0;
-        situation.board[PLAYER_2_SCORE_HOLE] = 0;
-
-        situation
-    }
-
-    pub fn act(&mut self, action: i32) -> i32 {
-        let actor = action / 10;
-        let hole_index = (action % 10 + (actor - 1) * 7 - 1) as usize;
-
-        // the game should not be ended
-        if self.ended {
-            return ILLEGAL;
-        }
-
-        // the actor should be correct
-        if actor != self.actor {
-            return ILLEGAL;
-        }
-
-        // the hole should not be empty
-        if self.board[hole_index] == 0 {
-            return ILLEGAL;
-        }
-
-        // get all pieces form this hole
-        let mut mancala_pieces = self.board[hole_index];
-        self.board[hole_index] = 0;
-
-        // sow!
-        let mut current_hole = Self::next_hole(hole_index);
-        while mancala_pieces > 0 {
-            // if opponent's score hole, skip it
-            if self.is_opponent_score_hole(current_hole) {
-                current_hole = Self::next_hole(current_hole);
-            }
-            // else, sow one piece in current hole
-            self.board[current_hole] += 1;
-            mancala_pieces -= 1;
-
-            // if this is the last piece
-            if mancala_pieces == 0 {
-                // move again
-                if self.can_move_again(current_hole) {
-                    self.actor = 3 - self.actor;
-                }
-                // critical hit
-                self.try_critical_hit(current_hole);
-            }
-            current_hole = Self::next_hole(current_hole);
-        }
-
-        // change actor
-        self.actor = 3 - self.actor;
-
-        if self.try_end() {
-            ENDED
-        } else {
-            NOT_ENDED
-        }
-    }
+#[wasm_bindgen]
+pub fn mancala_result(flag: i32, seq: &[i32], size: i32) -> i32 {
+    result(RuleSet::default(), flag, steps(seq, size))
+}
 
-    fn next_hole(hole_index: usize) -> usize {
-        (hole_index + 1usize) % HOLE_NUMBER
-    }
+/// `mancala_result` under another rule set, e.g. `"kalah(6,3)+empty-capture"`.
+#[wasm_bindgen]
+pub fn mancala_result_with_rules(rules: &str, flag: i32, seq: &[i32], size: i32) -> Result<i32, String> {
+    let rules = rules.parse::<RuleSet>().map_err(|error| error.to_string())?;
+    Ok(result(rules, flag, steps(seq, size)))
+}
 
-    fn is_opponent_score_hole(&self, hole_index: usize) -> bool {
-        self.actor == 1 && hole_index == PLAYER_2_SCORE_HOLE ||
-            self.actor == 2 && hole_index == PLAYER_1_SCORE_HOLE
-    }
+fn result(rules: RuleSet, flag: i32, seq: &[i32]) -> i32 {
+    let first_actor = Player::from_flag(flag);
+    let game_situation = match replay(rules, first_actor, seq) {
+        Ok(game_situation) => game_situation,
+        Err((step, _)) => return ILLEGAL + step as i32,
+    };
 
-    fn can_move_again(&self, hole_index: usize) -> bool {
-        self.actor == 1 && hole_index == PLAYER_1_SCORE_HOLE ||
-            self.actor == 2 && hole_index == PLAYER_2_SCORE_HOLE
+    if game_situation.ended() {
+        ENDED + game_situation.score(first_actor) -
+            game_situation.score(first_actor.opponent())
+    } else {
+        NOT_ENDED + game_situation.score(first_actor)
     }
+}
 
-    fn try_critical_hit(&mut self, hole_index: usize) {
-        if self.is_my_six_hole(hole_index) &&
-            self.board[hole_index] == 1 &&
-            self.board[self.opposite_hole(hole_index)] > 0 {
-            self.board[self.my_score_hole()] += 1 + self.board[self.opposite_hole(hole_index)];
-            self.board[hole_index] = 0;
-            self.board[self.opposite_hole(hole_index)] = 0;
-        }
-    }
+/// Explains the first illegal step of `seq`, e.g. `"step 1 (12): it is player
+/// 2's turn"`, or returns `undefined` if every step is legal.
+#[wasm_bindgen]
+pub fn mancala_result_error(flag: i32, seq: &[i32], size: i32) -> Option<String> {
+    replay(RuleSet::default(), Player::from_flag(flag), steps(seq, size))
+        .err()
+        .map(|(step, error)| format!("step {} ({}): {}", step, seq[step], error))
+}
 
-    fn my_score_hole(&self) -> usize {
-        if self.actor == 1 {
-            PLAYER_1_SCORE_HOLE
-        } else {
-            PLAYER_2_SCORE_HOLE
+/// `mancala_result` for a game of Oware, the score being the seeds captured.
+#[wasm_bindgen]
+pub fn oware_result(flag: i32, seq: &[i32], size: i32) -> i32 {
+    let first_actor = Player::from_flag(flag);
+    let mut oware_situation = OwareSituation::new(first_actor);
+    for (step, &code) in steps(seq, size).iter().enumerate() {
+        let result = Move::try_from(code)
+            .and_then(|mv| oware_situation.act(mv));
+        if result.is_err() {
+            return ILLEGAL + step as i32;
         }
     }
 
-    fn is_my_six_hole(&self, hole_index: usize) -> bool {
-        ((self.actor - 1) * 7) as usize <= hole_index &&
-            hole_index <= ((self.actor - 1) * 7 + 6) as usize
+    if oware_situation.ended() {
+        ENDED + oware_situation.score(first_actor) -
+            oware_situation.score(first_actor.opponent())
+    } else {
+        NOT_ENDED + oware_situation.score(first_actor)
     }
+}
 
-    fn opposite_hole(&self, hole_index: usize) -> usize {
-        assert!(
-            hole_index != PLAYER_1_SCORE_HOLE &&
-                hole_index != PLAYER_2_SCORE_HOLE
-        );
-        12 - hole_index
-    }
+// the first `size` steps of `seq`, none if `size` is negative
+fn steps(seq: &[i32], size: i32) -> &[i32] {
+    &seq[..(size.max(0) as usize).min(seq.len())]
+}
 
-    fn try_end(&mut self) -> bool {
-        let mut piece_number = 0;
-        for i in 0..6 {
-            piece_number += self.board[i];
-        }
-        if piece_number == 0 {
-            for i in 7..13 {
-                self.board[PLAYER_2_SCORE_HOLE] += self.board[i];
-                self.board[i] = 0;
-                self.ended = true;
-            }
-        }
-        piece_number = 0;
-        for i in 7..13 {
-            piece_number += self.board[i];
-        }
-        if piece_number == 0 {
-            for i in 0..6 {
-                self.board[PLAYER_1_SCORE_HOLE] += self.board[i];
-                self.board[i] = 0;
-                self.ended = true;
-            }
-        }
-        self.ended
+fn replay(
+    rules: RuleSet,
+    first_actor: Player,
+    seq: &[i32],
+) -> Result<GameSituation, (usize, IllegalMove)> {
+    let mut game_situation = GameSituation::with_rules(rules, first_actor);
+    for (step, &code) in seq.iter().enumerate() {
+        Move::try_from(code)
+            .and_then(|mv| game_situation.act(mv))
+            .map_err(|error| (step, error))?;
     }
+    Ok(game_situation)
 }
 
 #[cfg(test)]
+#[allow(clippy::identity_op)]
 mod tests {
     use super::*;
 
@@ -285,4 +180,65 @@ mod tests {
             23, 24
         ], 17), NOT_ENDED + 17);
     }
+
+    #[test]
+    fn test_case_size() {
+        assert_eq!(mancala_result(1, &[11, 22], -1), NOT_ENDED + 0);
+        assert_eq!(mancala_result(1, &[11, 22], 0), NOT_ENDED + 0);
+        assert_eq!(mancala_result(1, &[11, 12], 1), NOT_ENDED + 0);
+        assert_eq!(mancala_result(1, &[11, 12], 3), ILLEGAL + 1);
+        assert_eq!(mancala_result_error(1, &[11, 12], -2), None);
+        assert_eq!(mancala_result_error(1, &[11, 12], 9).as_deref(), Some("step 1 (12): it is player 2's turn"));
+        assert_eq!(mancala_result_with_rules("kalah(4,3)", 1, &[12], -1), Ok(NOT_ENDED + 0));
+        assert_eq!(mancala_result_with_rules("kalah(4,3)", 1, &[12], 2), Ok(NOT_ENDED + 1));
+        assert_eq!(oware_result(1, &[16], -1), NOT_ENDED + 0);
+        assert_eq!(oware_result(1, &[16, 16], 5), ILLEGAL + 1);
+    }
+
+    #[test]
+    fn test_case_error() {
+        assert_eq!(mancala_result_error(1, &[11, 22], 2), None);
+        assert_eq!(
+            mancala_result_error(1, &[11, 12], 2).as_deref(),
+            Some("step 1 (12): it is player 2's turn")
+        );
+        assert_eq!(
+            mancala_result_error(1, &[11, 21, 11], 3).as_deref(),
+            Some("step 2 (11): pit 1 is empty")
+        );
+        assert_eq!(
+            mancala_result_error(1, &[13, 17], 2).as_deref(),
+            Some("step 1 (17): 17 is not a move code")
+        );
+        assert_eq!(mancala_result(1, &[13, 17], 2), ILLEGAL + 1);
+        assert_eq!(mancala_result(1, &[31], 1), ILLEGAL);
+        assert_eq!(mancala_result_error(1, &[
+            11, 21, 12, 13, 25,
+            11, 21, 12, 22, 11,
+            23, 12, 24, 13, 11,
+            26, 12, 25, 11, 26,
+            11
+        ], 21).as_deref(), Some("step 20 (11): the game has already ended"));
+    }
+
+    #[test]
+    fn test_case_with_rules() {
+        assert_eq!(mancala_result_with_rules("kalah(6,4)", 1, &[11, 12], 2), Ok(ILLEGAL + 1));
+        assert_eq!(mancala_result_with_rules("kalah(6,3)", 1, &[14], 1), Ok(NOT_ENDED + 1));
+        assert_eq!(mancala_result_with_rules("kalah(4,3)", 1, &[12, 15], 2), Ok(ILLEGAL + 1));
+        assert_eq!(mancala_result_with_rules("kalah(4,3)", 1, &[12, 14], 2), Ok(NOT_ENDED + 2));
+        assert!(mancala_result_with_rules("oware", 1, &[11], 1).is_err());
+    }
+
+    #[test]
+    fn test_case_oware() {
+        assert_eq!(oware_result(1, &[16], 1), NOT_ENDED + 0);
+        assert_eq!(oware_result(1, &[16, 16], 2), ILLEGAL + 1);
+        assert_eq!(oware_result(2, &[21, 16, 22], 3), NOT_ENDED + 0);
+        assert_eq!(oware_result(2, &[21, 16, 16], 3), ILLEGAL + 2);
+        assert_eq!(oware_result(1, &[
+            11, 21, 12, 22, 13,
+            23, 14, 24
+        ], 8), NOT_ENDED + 0);
+    }
 }
@@ -1,203 +1,224 @@
-use wasm_bindgen::prelude::wasm_bindgen;
 use js_sys::Int32Array;
+use mancala_core::{GameSituation, Move, OwareSituation, Player, RuleSet, TOTAL_SEEDS};
+use wasm_bindgen::prelude::wasm_bindgen;
 
 #[wasm_bindgen]
 pub fn mancala_board(flag: i32, seq: &[i32], size: i32) -> Int32Array {
-    let mut game_situation = GameSituation::new(seq[0] / 10);
-    for i in 0..size - 1 {
-        game_situation.act(seq[i as usize]);
-    }
-    match game_situation.act(seq[(size - 1) as usize]) {
-        ILLEGAL => {
-            let mut tmp = [0; 15];
-            for i in 0..14 {
-                tmp[i] = game_situation.board[i];
-            }
-            if flag == 1 {
-                tmp[14] = 200 + 2 * game_situation.board[PLAYER_1_SCORE_HOLE] - 48;
-            } else {
-                tmp[14] = 200 - 2 * game_situation.board[PLAYER_2_SCORE_HOLE] + 48;
-            }
-            return Int32Array::from(&tmp[..]);
-        }
-        _ => {
-            let mut tmp = [0; 15];
-            for i in 0..14 {
-                tmp[i] = game_situation.board[i];
-            }
-
-            if game_situation.ended {
-                tmp[14] = 200 + game_situation.board[PLAYER_1_SCORE_HOLE] - game_situation.board[PLAYER_2_SCORE_HOLE];
-            } else {
-                tmp[14] = game_situation.actor;
-            }
-            return Int32Array::from(&tmp[..]);
-        }
-    }
+    Int32Array::from(&board(RuleSet::default(), flag, steps(seq, size))[..])
 }
 
-
-const HOLE_NUMBER: usize = 14;
-
-pub struct GameSituation {
-    pub actor: i32,
-    board: [i32; HOLE_NUMBER],
-    ended: bool,
+/// `mancala_board` under another rule set, e.g. `"kalah(4,3)"`. The board
+/// holds `2 * pits + 2` holes followed by the data bit.
+#[wasm_bindgen]
+pub fn mancala_board_with_rules(
+    rules: &str,
+    flag: i32,
+    seq: &[i32],
+    size: i32,
+) -> Result<Int32Array, String> {
+    let rules = rules.parse::<RuleSet>().map_err(|error| error.to_string())?;
+    Ok(Int32Array::from(&board(rules, flag, steps(seq, size))[..]))
 }
 
-const PLAYER_1_SCORE_HOLE: usize = 6;
-const PLAYER_2_SCORE_HOLE: usize = 13;
+// the first `size` steps of `seq`, none if `size` is negative
+fn steps(seq: &[i32], size: i32) -> &[i32] {
+    &seq[..(size.max(0) as usize).min(seq.len())]
+}
 
+fn board(rules: RuleSet, flag: i32, seq: &[i32]) -> Vec<i32> {
+    // no steps leave the board as it starts, with player `flag` to move
+    let Some((&last, played)) = seq.split_last() else {
+        return board_with_data(&GameSituation::with_rules(rules, Player::from_flag(flag)));
+    };
+    let mut game_situation = GameSituation::with_rules(rules, Player::from_flag(seq[0] / 10));
+    for &code in played {
+        if let Ok(mv) = Move::try_from(code) {
+            let _ = game_situation.act(mv);
+        }
+    }
 
-const ENDED: i32 = 15000;
-const NOT_ENDED: i32 = 20000;
-const ILLEGAL: i32 = 30000;
+    let result = Move::try_from(last)
+        .and_then(|mv| game_situation.act(mv));
 
-impl GameSituation {
-    pub fn new(first_actor: i32) -> GameSituation {
-        let mut situation = GameSituation {
-            actor: first_actor,
-            board: [4; HOLE_NUMBER],
-            ended: false,
+    let mut tmp = board_with_data(&game_situation);
+    if result.is_err() {
+        let total_seeds = rules.total_seeds();
+        *tmp.last_mut().unwrap() = if flag == 1 {
+            200 + 2 * game_situation.score(Player::One) - total_seeds
+        } else {
+            200 - 2 * game_situation.score(Player::Two) + total_seeds
         };
-
-        situation.board[PLAYER_1_SCORE_HOLE] = 0;
-        situation.board[PLAYER_2_SCORE_HOLE] = 0;
-
-        situation
     }
+    tmp
+}
 
-    pub fn act(&mut self, action: i32) -> i32 {
-        let actor = action / 10;
-        let hole_index = (action % 10 + (actor - 1) * 7 - 1) as usize;
+/// `mancala_board` for a game of Oware, the score holes holding the seeds
+/// each player has captured.
+#[wasm_bindgen]
+pub fn oware_board(flag: i32, seq: &[i32], size: i32) -> Int32Array {
+    Int32Array::from(&oware(flag, steps(seq, size))[..])
+}
 
-        // the game should not be ended
-        if self.ended {
-            return ILLEGAL;
+fn oware(flag: i32, seq: &[i32]) -> Vec<i32> {
+    let Some((&last, played)) = seq.split_last() else {
+        let mut tmp = OwareSituation::new(Player::from_flag(flag)).board().to_vec();
+        tmp.push(flag);
+        return tmp;
+    };
+    let mut oware_situation = OwareSituation::new(Player::from_flag(seq[0] / 10));
+    for &code in played {
+        if let Ok(mv) = Move::try_from(code) {
+            let _ = oware_situation.act(mv);
         }
+    }
 
-        // the actor should be correct
-        if actor != self.actor {
-            return ILLEGAL;
-        }
+    let result = Move::try_from(last)
+        .and_then(|mv| oware_situation.act(mv));
 
-        // the hole should not be empty
-        if self.board[hole_index] == 0 {
-            return ILLEGAL;
+    let mut tmp = oware_situation.board().to_vec();
+    tmp.push(if result.is_err() {
+        if flag == 1 {
+            200 + 2 * oware_situation.score(Player::One) - TOTAL_SEEDS
+        } else {
+            200 - 2 * oware_situation.score(Player::Two) + TOTAL_SEEDS
         }
+    } else if oware_situation.ended() {
+        200 + oware_situation.score(Player::One) - oware_situation.score(Player::Two)
+    } else {
+        oware_situation.actor().number()
+    });
+    tmp
+}
 
-        // get all pieces form this hole
-        let mut mancala_pieces = self.board[hole_index];
-        self.board[hole_index] = 0;
-
-        // sow!
-        let mut current_hole = Self::next_hole(hole_index);
-        while mancala_pieces > 0 {
-            // if opponent's score hole, skip it
-            if self.is_opponent_score_hole(current_hole) {
-                current_hole = Self::next_hole(current_hole);
-            }
-            // else, sow one piece in current hole
-            self.board[current_hole] += 1;
-            mancala_pieces -= 1;
+/// A game that JS can step through move by move, backwards and forwards,
+/// without replaying the whole sequence through `mancala_board`.
+#[wasm_bindgen]
+pub struct MancalaSession {
+    game_situation: GameSituation,
+}
 
-            // if this is the last piece
-            if mancala_pieces == 0 {
-                // move again
-                if self.can_move_again(current_hole) {
-                    self.actor = 3 - self.actor;
-                }
-                // critical hit
-                self.try_critical_hit(current_hole);
-            }
-            current_hole = Self::next_hole(current_hole);
+#[wasm_bindgen]
+impl MancalaSession {
+    #[wasm_bindgen(constructor)]
+    pub fn new(flag: i32) -> MancalaSession {
+        MancalaSession {
+            game_situation: GameSituation::new(Player::from_flag(flag)),
         }
+    }
 
-        // change actor
-        self.actor = 3 - self.actor;
-
-        if self.try_end() {
-            ENDED
-        } else {
-            NOT_ENDED
-        }
+    /// A session under another rule set, e.g. `"kalah(6,6)+sweep-to-opponent"`.
+    pub fn with_rules(rules: &str, flag: i32) -> Result<MancalaSession, String> {
+        let rules = rules.parse::<RuleSet>().map_err(|error| error.to_string())?;
+        Ok(MancalaSession {
+            game_situation: GameSituation::with_rules(rules, Player::from_flag(flag)),
+        })
     }
 
-    fn next_hole(hole_index: usize) -> usize {
-        (hole_index + 1usize) % HOLE_NUMBER
+    /// Plays the move `code`, throwing the reason if it is illegal.
+    pub fn act(&mut self, code: i32) -> Result<(), String> {
+        Move::try_from(code)
+            .and_then(|mv| self.game_situation.act(mv))
+            .map(|_| ())
+            .map_err(|error| error.to_string())
     }
 
-    fn is_opponent_score_hole(&self, hole_index: usize) -> bool {
-        self.actor == 1 && hole_index == PLAYER_2_SCORE_HOLE ||
-            self.actor == 2 && hole_index == PLAYER_1_SCORE_HOLE
+    /// Takes back the last move and returns its code, or `undefined` at the
+    /// start of the game.
+    pub fn undo(&mut self) -> Option<i32> {
+        self.game_situation.undo().map(|(mv, _)| mv.code())
     }
 
-    fn can_move_again(&self, hole_index: usize) -> bool {
-        self.actor == 1 && hole_index == PLAYER_1_SCORE_HOLE ||
-            self.actor == 2 && hole_index == PLAYER_2_SCORE_HOLE
+    /// Plays again the last undone move and returns its code, or `undefined`
+    /// if there is nothing to redo.
+    pub fn redo(&mut self) -> Option<i32> {
+        self.game_situation.redo().map(|(mv, _)| mv.code())
     }
 
-    fn try_critical_hit(&mut self, hole_index: usize) {
-        if self.is_my_six_hole(hole_index) &&
-            self.board[hole_index] == 1 &&
-            self.board[self.opposite_hole(hole_index)] > 0 {
-            self.board[self.my_score_hole()] += 1 + self.board[self.opposite_hole(hole_index)];
-            self.board[hole_index] = 0;
-            self.board[self.opposite_hole(hole_index)] = 0;
-        }
+    /// The moves played so far.
+    pub fn history(&self) -> Vec<i32> {
+        self.game_situation.history().map(Move::code).collect()
     }
 
-    fn my_score_hole(&self) -> usize {
-        if self.actor == 1 {
-            PLAYER_1_SCORE_HOLE
-        } else {
-            PLAYER_2_SCORE_HOLE
-        }
+    /// The board in the same format as `mancala_board`.
+    pub fn board(&self) -> Int32Array {
+        Int32Array::from(&board_with_data(&self.game_situation)[..])
     }
+}
+
+// holes and score holes, followed by the next actor or 200 + the net score of
+// player 1 once the game has ended
+fn board_with_data(game_situation: &GameSituation) -> Vec<i32> {
+    let mut tmp = game_situation.board().to_vec();
+    tmp.push(if game_situation.ended() {
+        200 + game_situation.score(Player::One) - game_situation.score(Player::Two)
+    } else {
+        game_situation.actor().number()
+    });
+    tmp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    fn is_my_six_hole(&self, hole_index: usize) -> bool {
-        ((self.actor - 1) * 7) as usize <= hole_index &&
-            hole_index <= ((self.actor - 1) * 7 + 6) as usize
+    #[test]
+    fn test_session_steps() {
+        let mut session = MancalaSession::new(1);
+        assert_eq!(session.act(13), Ok(()));
+        assert_eq!(session.act(21), Err("it is player 1's turn".to_string()));
+        assert_eq!(session.act(11), Ok(()));
+        assert_eq!(session.history(), [13, 11]);
+        assert_eq!(session.undo(), Some(11));
+        assert_eq!(session.undo(), Some(13));
+        assert_eq!(session.undo(), None);
+        assert_eq!(session.redo(), Some(13));
+        assert_eq!(session.history(), [13]);
+        assert_eq!(session.act(12), Ok(()));
+        assert_eq!(session.redo(), None);
     }
 
-    fn opposite_hole(&self, hole_index: usize) -> usize {
-        assert!(
-            hole_index != PLAYER_1_SCORE_HOLE &&
-                hole_index != PLAYER_2_SCORE_HOLE
-        );
-        12 - hole_index
+    #[test]
+    fn test_board_with_rules() {
+        assert_eq!(board(RuleSet::kalah(4, 3), 1, &[12]), [3, 0, 4, 4, 1, 3, 3, 3, 3, 0, 1]);
+        assert_eq!(board(RuleSet::kalah(4, 3), 1, &[12, 21]), [3, 0, 4, 4, 1, 3, 3, 3, 3, 0, 200 + 2 - 24]);
+        assert_eq!(board(RuleSet::default(), 2, &[11, 11]), [
+            0, 5, 5, 5, 5, 4, 0,
+            4, 4, 4, 4, 4, 4, 0,
+            200 + 48
+        ]);
     }
 
-    fn try_end(&mut self) -> bool {
-        let mut piece_number = 0;
-        for i in 0..6 {
-            piece_number += self.board[i];
-        }
-        if piece_number == 0 {
-            for i in 7..13 {
-                self.board[PLAYER_2_SCORE_HOLE] += self.board[i];
-                self.board[i] = 0;
-                self.ended = true;
-            }
-        }
-        piece_number = 0;
-        for i in 7..13 {
-            piece_number += self.board[i];
-        }
-        if piece_number == 0 {
-            for i in 0..6 {
-                self.board[PLAYER_1_SCORE_HOLE] += self.board[i];
-                self.board[i] = 0;
-                self.ended = true;
-            }
-        }
-        self.ended
+    #[test]
+    fn test_board_size() {
+        let start = board(RuleSet::default(), 2, &[]);
+        assert_eq!(start, [
+            4, 4, 4, 4, 4, 4, 0,
+            4, 4, 4, 4, 4, 4, 0,
+            2
+        ]);
+        assert_eq!(board(RuleSet::default(), 2, steps(&[13, 11], -1)), start);
+        assert_eq!(board(RuleSet::default(), 2, steps(&[13, 11], 5)), board(RuleSet::default(), 2, &[13, 11]));
+        assert_eq!(oware(1, steps(&[16], -1)), [
+            4, 4, 4, 4, 4, 4, 0,
+            4, 4, 4, 4, 4, 4, 0,
+            1
+        ]);
+        assert_eq!(oware(1, steps(&[16], 3)), oware(1, &[16]));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    // use super::*;
+    #[test]
+    fn test_oware_board() {
+        assert_eq!(oware(1, &[16]), [
+            4, 4, 4, 4, 4, 0, 0,
+            5, 5, 5, 5, 4, 4, 0,
+            2
+        ]);
+        assert_eq!(oware(1, &[16, 16]), [
+            4, 4, 4, 4, 4, 0, 0,
+            5, 5, 5, 5, 4, 4, 0,
+            200 - TOTAL_SEEDS
+        ]);
+    }
 
     #[test]
     fn test_case_illegal() {
@@ -0,0 +1,24 @@
+// generates the endgame table the `endgame-table` feature embeds
+
+#[allow(dead_code)]
+#[path = "src/endgame.rs"]
+mod endgame;
+
+// seeds in play of the embedded table, about 250 KB
+const DEFAULT_SEEDS: usize = 8;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/endgame.rs");
+    println!("cargo:rerun-if-env-changed=MANCALA_ENDGAME_SEEDS");
+    if std::env::var_os("CARGO_FEATURE_ENDGAME_TABLE").is_none() {
+        return;
+    }
+
+    let seeds = match std::env::var("MANCALA_ENDGAME_SEEDS") {
+        Ok(seeds) => seeds.parse().expect("MANCALA_ENDGAME_SEEDS is a number of seeds"),
+        Err(_) => DEFAULT_SEEDS,
+    };
+    let table = endgame::EndgameTable::generate(seeds);
+    let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("endgame.bin");
+    std::fs::write(out, table.to_bytes()).unwrap();
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use mancala_core::{GameSituation, Move, Player, RuleSet, HOLE_NUMBER};
+
+/// Best moves of standard Kalah positions, found ahead of time.
+///
+/// The text form has one position per line: the flag of the player to move,
+/// the 14 holes of the board and the move, separated by spaces. Empty lines
+/// and lines starting with `#` are skipped.
+#[derive(Clone, Debug, Default)]
+pub struct OpeningBook {
+    moves: HashMap<(Player, [i32; HOLE_NUMBER]), Move>,
+}
+
+impl OpeningBook {
+    pub fn new() -> OpeningBook {
+        OpeningBook::default()
+    }
+
+    pub fn parse(text: &str) -> Result<OpeningBook, String> {
+        let mut book = OpeningBook::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let numbers = line
+                .split_whitespace()
+                .map(|number| number.parse::<i32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|error| format!("line {}: {}", i + 1, error))?;
+            if numbers.len() != HOLE_NUMBER + 2 {
+                return Err(format!("line {}: expected {} numbers", i + 1, HOLE_NUMBER + 2));
+            }
+            let actor = Player::from_flag(numbers[0]);
+            let mv = Move::try_from(numbers[HOLE_NUMBER + 1]).map_err(|error| format!("line {}: {}", i + 1, error))?;
+            if mv.player != actor {
+                return Err(format!("line {}: {} is not a move of player {}", i + 1, mv.code(), actor.number()));
+            }
+            let mut board = [0; HOLE_NUMBER];
+            board.copy_from_slice(&numbers[1..HOLE_NUMBER + 1]);
+            book.moves.insert((actor, board), mv);
+        }
+        Ok(book)
+    }
+
+    /// Books `mv` for `game_situation`, which must be a standard Kalah
+    /// position, as `lookup` answers no other.
+    pub fn insert(&mut self, game_situation: &GameSituation, mv: Move) -> Result<(), String> {
+        if *game_situation.rules() != RuleSet::default() {
+            return Err(format!("cannot book a position of {}", game_situation.rules()));
+        }
+        self.moves.insert(key(game_situation), mv);
+        Ok(())
+    }
+
+    /// The book move of `game_situation`, if it is a standard Kalah position
+    /// in the book.
+    pub fn lookup(&self, game_situation: &GameSituation) -> Option<Move> {
+        if *game_situation.rules() != RuleSet::default() || game_situation.ended() {
+            return None;
+        }
+        self.moves.get(&key(game_situation)).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+}
+
+fn key(game_situation: &GameSituation) -> (Player, [i32; HOLE_NUMBER]) {
+    let mut board = [0; HOLE_NUMBER];
+    board.copy_from_slice(game_situation.board());
+    (game_situation.actor(), board)
+}
+
+/// The text form `parse` reads, sorted so that rebuilding a book gives a
+/// readable diff.
+impl fmt::Display for OpeningBook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut entries = self.moves.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|&(&(actor, board), _)| (actor.number(), board.map(|seeds| -seeds)));
+        for ((actor, board), mv) in entries {
+            write!(f, "{}", actor.number())?;
+            for seeds in board {
+                write!(f, " {}", seeds)?;
+            }
+            writeln!(f, " {}", mv.code())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut book = OpeningBook::new();
+        let mut game_situation = GameSituation::new(Player::One);
+        book.insert(&game_situation, Move::new(Player::One, 3)).unwrap();
+        game_situation.act(Move::new(Player::One, 3)).unwrap();
+        game_situation.act(Move::new(Player::One, 6)).unwrap();
+        book.insert(&game_situation, Move::new(Player::Two, 1)).unwrap();
+
+        let text = book.to_string();
+        assert_eq!(text.lines().next(), Some("1 4 4 4 4 4 4 0 4 4 4 4 4 4 0 13"));
+        let read = OpeningBook::parse(&format!("# a comment\n\n{}", text)).unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read.lookup(&game_situation), Some(Move::new(Player::Two, 1)));
+        assert_eq!(read.lookup(&GameSituation::new(Player::Two)), None);
+        let small = GameSituation::with_rules(RuleSet::kalah(4, 4), Player::One);
+        assert_eq!(read.lookup(&small), None);
+
+        let mut other = GameSituation::with_rules(RuleSet::kalah(4, 3), Player::One);
+        assert!(book.insert(&other, Move::new(Player::One, 1)).is_err());
+        other.act(Move::new(Player::One, 2)).unwrap();
+        assert!(book.insert(&other, Move::new(Player::One, 1)).is_err());
+        assert_eq!(book.len(), 2);
+
+        assert!(OpeningBook::parse("1 4 4 4 4 4 4 0 4 4 4 4 4 4 0").is_err());
+        assert!(OpeningBook::parse("1 4 4 4 4 4 4 0 4 4 4 4 4 4 0 23").is_err());
+    }
+}
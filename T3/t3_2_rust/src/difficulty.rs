@@ -0,0 +1,195 @@
+//! Weaker play for people to beat: shallower searches, blurred values and
+//! the odd mistake, all drawn from a seeded generator.
+
+use std::fmt;
+use std::str::FromStr;
+
+use mancala_core::{GameSituation, Move};
+
+use crate::eval::Evaluator;
+use crate::rng::Rng;
+use crate::search::Search;
+use crate::tt;
+
+/// The named difficulty levels, from the weakest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Beginner,
+    Easy,
+    Medium,
+    Hard,
+    /// The operator at full strength.
+    Expert,
+}
+
+impl Level {
+    /// How the level plays, or `None` for the full-strength operator.
+    pub fn difficulty(self) -> Option<Difficulty> {
+        let (max_depth, noise, blunder, temperature) = match self {
+            Level::Beginner => (1, 4.0, 0.3, 2.0),
+            Level::Easy => (2, 2.0, 0.15, 1.0),
+            Level::Medium => (4, 1.0, 0.05, 0.5),
+            Level::Hard => (8, 0.0, 0.0, 0.2),
+            Level::Expert => return None,
+        };
+        Some(Difficulty { max_depth, noise, blunder, temperature, seed: 1 })
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Level, String> {
+        match s {
+            "beginner" => Ok(Level::Beginner),
+            "easy" => Ok(Level::Easy),
+            "medium" => Ok(Level::Medium),
+            "hard" => Ok(Level::Hard),
+            "expert" => Ok(Level::Expert),
+            _ => Err(format!("unknown difficulty level `{}`", s)),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Level::Beginner => "beginner",
+            Level::Easy => "easy",
+            Level::Medium => "medium",
+            Level::Hard => "hard",
+            Level::Expert => "expert",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Difficulty {
+    /// Most turns looked ahead.
+    pub max_depth: i32,
+    /// The most seeds added to or taken from the value of every move.
+    pub noise: f32,
+    /// The probability of playing any move but the best.
+    pub blunder: f32,
+    /// The softmax temperature over the values of the moves, in seeds; 0
+    /// always plays the best.
+    pub temperature: f32,
+    /// Together with the position, fixes every random choice.
+    pub seed: u64,
+}
+
+/// A move for the actor of `game_situation` played at `difficulty` within
+/// about `time_budget` milliseconds, or `None` if there is no legal move. The
+/// same position and seed give the same move, unless the time runs out
+/// before `max_depth`.
+pub fn choose<E: Evaluator>(
+    search: &mut Search<E>,
+    game_situation: &mut GameSituation,
+    difficulty: &Difficulty,
+    time_budget: f64,
+) -> Option<Move> {
+    let mut rng = Rng::new(difficulty.seed ^ tt::hash(game_situation));
+    let mut analyses = search.analyze_iteratively(game_situation, difficulty.max_depth.max(1), time_budget);
+    for analysis in &mut analyses {
+        analysis.value += (rng.unit() * 2.0 - 1.0) * difficulty.noise;
+    }
+    analyses.sort_by(|a, b| b.value.total_cmp(&a.value));
+
+    if analyses.len() > 1 && rng.unit() < difficulty.blunder {
+        return Some(analyses[1 + rng.below(analyses.len() - 1)].mv);
+    }
+    if difficulty.temperature <= 0.0 {
+        return analyses.first().map(|analysis| analysis.mv);
+    }
+    let best = analyses.first()?.value;
+    let weights = analyses
+        .iter()
+        .map(|analysis| ((analysis.value - best) / difficulty.temperature).exp())
+        .collect::<Vec<_>>();
+    let mut pick = rng.unit() * weights.iter().sum::<f32>();
+    for (analysis, weight) in analyses.iter().zip(&weights) {
+        if pick < *weight {
+            return Some(analysis.mv);
+        }
+        pick -= weight;
+    }
+    analyses.last().map(|analysis| analysis.mv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mancala_core::Player;
+
+    #[test]
+    fn test_level_names() {
+        for level in [Level::Beginner, Level::Easy, Level::Medium, Level::Hard, Level::Expert] {
+            assert_eq!(level.to_string().parse(), Ok(level));
+        }
+        assert!("impossible".parse::<Level>().is_err());
+        assert_eq!(Level::Expert.difficulty(), None);
+    }
+
+    #[test]
+    fn test_choose() {
+        // 11 captures the 10 seeds of 25 and wins; anything else loses them
+        let mut game_situation = GameSituation::from(Player::One, &[
+            1, 0, 0, 1, 1, 0, 19,
+            1, 1, 1, 1, 10, 1, 11
+        ]);
+        let before = game_situation.clone();
+        let strict = Difficulty { max_depth: 2, noise: 0.0, blunder: 0.0, temperature: 0.0, seed: 1 };
+        assert_eq!(choose(&mut Search::new(), &mut game_situation, &strict, f64::INFINITY), Some(Move::new(Player::One, 1)));
+        assert_eq!(game_situation, before);
+
+        // beginners miss it now and then, the same way for the same seed
+        let beginner = Level::Beginner.difficulty().unwrap();
+        let moves = (0..20)
+            .map(|seed| choose(&mut Search::new(), &mut game_situation, &Difficulty { seed, ..beginner }, f64::INFINITY))
+            .collect::<Vec<_>>();
+        assert!(moves.iter().any(|&mv| mv != Some(Move::new(Player::One, 1))));
+        assert_eq!(choose(&mut Search::new(), &mut game_situation, &Difficulty { seed: 3, ..beginner }, f64::INFINITY), moves[3]);
+    }
+
+    #[test]
+    fn test_handicaps() {
+        // 11 captures the 10 seeds of 25, 14 and 15 leave them to player
+        // two
+        let mut game_situation = GameSituation::from(Player::One, &[
+            1, 0, 0, 1, 1, 0, 19,
+            1, 1, 1, 1, 10, 1, 11
+        ]);
+        let capture = Some(Move::new(Player::One, 1));
+        let strict = Difficulty { max_depth: 2, noise: 0.0, blunder: 0.0, temperature: 0.0, seed: 1 };
+        let moves = |difficulty: Difficulty, game_situation: &mut GameSituation| {
+            (0..40)
+                .map(|seed| choose(&mut Search::new(), game_situation, &Difficulty { seed, ..difficulty }, f64::INFINITY))
+                .collect::<Vec<_>>()
+        };
+
+        // noise smaller than the gain of the capture never hides it, larger
+        // noise sometimes does
+        assert!(moves(Difficulty { noise: 1.0, ..strict }, &mut game_situation).iter().all(|&mv| mv == capture));
+        assert!(moves(Difficulty { noise: 50.0, ..strict }, &mut game_situation).iter().any(|&mv| mv != capture));
+
+        // a sure blunder is never the best move
+        assert!(!moves(Difficulty { blunder: 1.0, ..strict }, &mut game_situation).contains(&capture));
+
+        // a high temperature plays every move, a low one the best
+        let mut hot = moves(Difficulty { temperature: 100.0, ..strict }, &mut game_situation);
+        hot.sort_by_key(|mv| mv.map(|mv| mv.pit));
+        hot.dedup();
+        assert_eq!(hot.len(), 3);
+        assert!(moves(Difficulty { temperature: 0.1, ..strict }, &mut game_situation).iter().all(|&mv| mv == capture));
+    }
+
+    #[test]
+    fn test_choose_out_of_time() {
+        let mut game_situation = GameSituation::new(Player::One);
+        let mut search = Search::new();
+        let hard = Level::Hard.difficulty().unwrap();
+        assert!(choose(&mut search, &mut game_situation, &hard, 0.0).is_some());
+        assert_eq!(search.completed_depth, 1);
+        assert_eq!(game_situation, GameSituation::new(Player::One));
+    }
+}
@@ -0,0 +1,292 @@
+//! Perfect-play values of standard Kalah positions with few seeds left in
+//! the pits.
+//!
+//! What is left to win depends only on the seeds in the twelve pits and on
+//! who moves, so the table holds, for every distribution of at most
+//! `max_seeds` seeds and either player to move, how many more seeds the
+//! player to move will gain than the opponent from there on. Seeds only leave
+//! the pits, and a sowing that keeps them all there moves them closer to the
+//! mover's score hole, so the positions can be solved from the emptiest
+//! upwards without ever meeting a cycle.
+//!
+//! This file is also built into `build.rs`, so it must not use the rest of
+//! the crate.
+
+use std::borrow::Cow;
+
+use mancala_core::{GameSituation, Move, Player, RuleSet, HOLE_NUMBER, PIT_NUMBER};
+
+/// Most seeds in play a table can be generated for, already 1.25 G entries.
+pub const MAX_TABLE_SEEDS: usize = 24;
+
+// both sides of the standard board
+const PITS: usize = 2 * PIT_NUMBER;
+
+// the first bytes of a table file, then `max_seeds` and the values
+const MAGIC: &[u8; 4] = b"KEGT";
+
+// not solved yet, while generating
+const UNKNOWN: u8 = i8::MIN as u8;
+
+const fn binomials() -> [[usize; PITS + 1]; MAX_TABLE_SEEDS + PITS + 1] {
+    let mut binomials = [[0; PITS + 1]; MAX_TABLE_SEEDS + PITS + 1];
+    let mut n = 0;
+    while n < MAX_TABLE_SEEDS + PITS + 1 {
+        binomials[n][0] = 1;
+        let mut k = 1;
+        while k <= PITS && k <= n {
+            binomials[n][k] = binomials[n - 1][k - 1] + binomials[n - 1][k];
+            k += 1;
+        }
+        n += 1;
+    }
+    binomials
+}
+
+const BINOMIALS: [[usize; PITS + 1]; MAX_TABLE_SEEDS + PITS + 1] = binomials();
+
+// n choose k, where n may be anything `index` needs
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n { 0 } else { BINOMIALS[n][k] }
+}
+
+// distributions of fewer than `seeds` seeds over the pits
+fn level_offset(seeds: usize) -> usize {
+    binomial(seeds + PITS - 1, PITS)
+}
+
+// distributions of exactly `seeds` seeds over the pits
+fn level_size(seeds: usize) -> usize {
+    binomial(seeds + PITS - 1, PITS - 1)
+}
+
+// the holes of the pits, player one's then player two's
+fn pit_holes() -> impl Iterator<Item = usize> {
+    (0..PIT_NUMBER).chain(PIT_NUMBER + 1..2 * PIT_NUMBER + 1)
+}
+
+// the rank of `pits` among every distribution with as many seeds or fewer,
+// in lexicographic order within each number of seeds
+fn index(pits: &[usize; PITS]) -> usize {
+    let seeds = pits.iter().sum::<usize>();
+    let mut rank = level_offset(seeds);
+    let mut left = seeds;
+    for (i, &pit) in pits[..PITS - 1].iter().enumerate() {
+        // distributions of `left` over the pits from i on with fewer seeds in i
+        let after = PITS - 1 - i;
+        rank += binomial(left + after, after) - binomial(left - pit + after, after);
+        left -= pit;
+    }
+    rank
+}
+
+// the distribution of `seeds` seeds at `rank` within its level
+fn pits_at(seeds: usize, mut rank: usize) -> [usize; PITS] {
+    let mut pits = [0; PITS];
+    let mut left = seeds;
+    for (i, pit) in pits[..PITS - 1].iter_mut().enumerate() {
+        let after = PITS - 1 - i;
+        loop {
+            let count = binomial(left - *pit + after - 1, after - 1);
+            if rank < count {
+                break;
+            }
+            rank -= count;
+            *pit += 1;
+        }
+        left -= *pit;
+    }
+    pits[PITS - 1] = left;
+    pits
+}
+
+/// Values of every standard Kalah position with at most `max_seeds` seeds in
+/// the pits.
+#[derive(Clone, Debug)]
+pub struct EndgameTable {
+    max_seeds: usize,
+    // i8 per position and player to move
+    values: Cow<'static, [u8]>,
+}
+
+impl EndgameTable {
+    /// Solves every position with at most `max_seeds` seeds in play.
+    pub fn generate(max_seeds: usize) -> EndgameTable {
+        assert!(max_seeds <= MAX_TABLE_SEEDS, "at most {} seeds", MAX_TABLE_SEEDS);
+        let mut values = vec![UNKNOWN; 2 * level_offset(max_seeds + 1)];
+        for seeds in 0..=max_seeds {
+            for rank in 0..level_size(seeds) {
+                let pits = pits_at(seeds, rank);
+                for player in [Player::One, Player::Two] {
+                    solve(&mut values, &pits, player);
+                }
+            }
+        }
+        EndgameTable { max_seeds, values: Cow::Owned(values) }
+    }
+
+    /// Reads a table written by `to_bytes`, without copying it.
+    pub fn from_bytes(bytes: &'static [u8]) -> Option<EndgameTable> {
+        let table = EndgameTable::parse(bytes)?;
+        Some(EndgameTable { max_seeds: table.0, values: Cow::Borrowed(table.1) })
+    }
+
+    /// `from_bytes` for bytes that don't live forever.
+    pub fn from_vec(bytes: Vec<u8>) -> Option<EndgameTable> {
+        let (max_seeds, values) = EndgameTable::parse(&bytes)?;
+        Some(EndgameTable { max_seeds, values: Cow::Owned(values.to_vec()) })
+    }
+
+    fn parse(bytes: &[u8]) -> Option<(usize, &[u8])> {
+        let values = bytes.strip_prefix(MAGIC)?;
+        let (&max_seeds, values) = values.split_first()?;
+        let max_seeds = max_seeds as usize;
+        if max_seeds > MAX_TABLE_SEEDS || values.len() != 2 * level_offset(max_seeds + 1) {
+            return None;
+        }
+        Some((max_seeds, values))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(self.max_seeds as u8);
+        bytes.extend_from_slice(&self.values);
+        bytes
+    }
+
+    pub fn max_seeds(&self) -> usize {
+        self.max_seeds
+    }
+
+    /// How many more seeds the actor of `game_situation` will gain than the
+    /// opponent with perfect play, or `None` if the position is not in the
+    /// table.
+    pub fn probe(&self, game_situation: &GameSituation) -> Option<i32> {
+        if *game_situation.rules() != RuleSet::default() || game_situation.ended() {
+            return None;
+        }
+        let board = game_situation.board();
+        let mut pits = [0; PITS];
+        for (pit, hole) in pits.iter_mut().zip(pit_holes()) {
+            *pit = board[hole] as usize;
+        }
+        if pits.iter().sum::<usize>() > self.max_seeds {
+            return None;
+        }
+        Some(value_at(&self.values, &pits, game_situation.actor()) as i32)
+    }
+}
+
+fn slot(pits: &[usize; PITS], player: Player) -> usize {
+    2 * index(pits) + (player == Player::Two) as usize
+}
+
+fn value_at(values: &[u8], pits: &[usize; PITS], player: Player) -> i8 {
+    values[slot(pits, player)] as i8
+}
+
+// fills in the value of `pits` with `player` to move, and of the positions of
+// as many seeds it leads to
+fn solve(values: &mut [u8], pits: &[usize; PITS], player: Player) -> i8 {
+    let known = values[slot(pits, player)];
+    if known != UNKNOWN {
+        return known as i8;
+    }
+
+    let mut status = [0; HOLE_NUMBER];
+    for (hole, &pit) in pit_holes().zip(pits) {
+        status[hole] = pit as i32;
+    }
+    let value = if pits[..PIT_NUMBER].iter().sum::<usize>() == 0 || pits[PIT_NUMBER..].iter().sum::<usize>() == 0 {
+        // the game has ended, and the sweep is already decided
+        let own = if player == Player::One { &pits[..PIT_NUMBER] } else { &pits[PIT_NUMBER..] };
+        2 * own.iter().sum::<usize>() as i32 - pits.iter().sum::<usize>() as i32
+    } else {
+        let mut best = i32::MIN;
+        for pit in 1..=PIT_NUMBER {
+            let mut game_situation = GameSituation::from(player, &status);
+            if game_situation.act(Move::new(player, pit)).is_err() {
+                continue;
+            }
+            let gain = game_situation.score(player) - game_situation.score(player.opponent());
+            let value = if game_situation.ended() {
+                gain
+            } else {
+                let board = game_situation.board();
+                let mut next = [0; PITS];
+                for (next_pit, hole) in next.iter_mut().zip(pit_holes()) {
+                    *next_pit = board[hole] as usize;
+                }
+                let next_value = solve(values, &next, game_situation.actor()) as i32;
+                if game_situation.actor() == player { gain + next_value } else { gain - next_value }
+            };
+            best = best.max(value);
+        }
+        best
+    };
+
+    values[slot(pits, player)] = value as i8 as u8;
+    value as i8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::Search;
+
+    #[test]
+    fn test_index() {
+        let mut expected = 0;
+        for seeds in 0..=3 {
+            for rank in 0..level_size(seeds) {
+                let pits = pits_at(seeds, rank);
+                assert_eq!(pits.iter().sum::<usize>(), seeds);
+                assert_eq!(index(&pits), expected);
+                expected += 1;
+            }
+        }
+        assert_eq!(expected, level_offset(4));
+        assert_eq!(index(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]), 1);
+        assert_eq!(index(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]), 12);
+    }
+
+    #[test]
+    fn test_matches_search() {
+        let table = EndgameTable::generate(5);
+        assert_eq!(table.max_seeds(), 5);
+        for mut game_situation in [
+            GameSituation::from(Player::One, &[
+                0, 0, 0, 0, 1, 1, 20,
+                0, 0, 0, 0, 1, 0, 25
+            ]),
+            GameSituation::from(Player::Two, &[
+                1, 0, 0, 2, 0, 0, 22,
+                0, 1, 0, 0, 0, 1, 21
+            ]),
+            GameSituation::from(Player::One, &[
+                0, 0, 1, 0, 0, 0, 23,
+                4, 0, 0, 0, 0, 0, 20
+            ]),
+        ] {
+            let actor = game_situation.actor();
+            let (_, value) = Search::new().best_move(&mut game_situation, 64).unwrap();
+            let scores = game_situation.score(actor) - game_situation.score(actor.opponent());
+            assert_eq!(table.probe(&game_situation), Some(value as i32 - scores));
+        }
+
+        assert_eq!(table.probe(&GameSituation::new(Player::One)), None);
+        let small = GameSituation::with_rules(RuleSet::kalah(4, 1), Player::One);
+        assert_eq!(table.probe(&small), None);
+    }
+
+    #[test]
+    fn test_bytes() {
+        let table = EndgameTable::generate(3);
+        let bytes = table.to_bytes();
+        let read = EndgameTable::from_vec(bytes.clone()).unwrap();
+        assert_eq!(read.max_seeds(), 3);
+        assert_eq!(read.values, table.values);
+        assert!(EndgameTable::from_vec(bytes[..bytes.len() - 1].to_vec()).is_none());
+        assert!(EndgameTable::from_vec(b"KEGS".to_vec()).is_none());
+    }
+}
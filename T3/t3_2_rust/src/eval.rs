@@ -0,0 +1,207 @@
+use mancala_core::{GameSituation, Player, RuleSet, Sweep};
+
+/// Static evaluation of the positions where the search stops.
+pub trait Evaluator {
+    /// How good `game_situation` is for player one. Ended games should be
+    /// valued by their final scores alone.
+    fn value(&self, game_situation: &GameSituation) -> f32;
+
+    /// The value of a game that ends with the player it is valued for `lead`
+    /// seeds ahead. `value` of an ended game should agree with it.
+    fn final_value(&self, lead: i32) -> f32 {
+        lead as f32
+    }
+
+    /// `value` from the point of view of `decide_for`.
+    fn value_for(&self, game_situation: &GameSituation, decide_for: Player) -> f32 {
+        match decide_for {
+            Player::One => { self.value(game_situation) }
+            Player::Two => { -self.value(game_situation) }
+        }
+    }
+}
+
+/// The baseline: the difference of the two score holes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Difference;
+
+impl Evaluator for Difference {
+    // E(score) for player one
+    fn value(&self, game_situation: &GameSituation) -> f32 {
+        (game_situation.score(Player::One) - game_situation.score(Player::Two)) as f32
+    }
+}
+
+pub const FEATURE_NUMBER: usize = 6;
+
+/// Names of the terms of `features`, in order.
+pub const FEATURE_NAMES: [&str; FEATURE_NUMBER] = [
+    "store",
+    "material",
+    "capturable",
+    "free_moves",
+    "vulnerability",
+    "sweep",
+];
+
+/// Weights `FeatureEvaluator` starts with, as tuned by `mancala-tune` in
+/// `/mancala-tools`.
+pub const DEFAULT_WEIGHTS: [f32; FEATURE_NUMBER] = include!("weights.in");
+
+/// A weighted sum of `features`. Weights `[1, 0, 0, 0, 0, 0]` value positions
+/// exactly as `Difference` does.
+#[derive(Clone, Copy, Debug)]
+pub struct FeatureEvaluator {
+    pub weights: [f32; FEATURE_NUMBER],
+}
+
+impl FeatureEvaluator {
+    pub fn new(weights: [f32; FEATURE_NUMBER]) -> FeatureEvaluator {
+        FeatureEvaluator { weights }
+    }
+}
+
+impl Default for FeatureEvaluator {
+    fn default() -> FeatureEvaluator {
+        FeatureEvaluator::new(DEFAULT_WEIGHTS)
+    }
+}
+
+impl Evaluator for FeatureEvaluator {
+    fn value(&self, game_situation: &GameSituation) -> f32 {
+        features(game_situation)
+            .iter()
+            .zip(self.weights)
+            .map(|(feature, weight)| feature * weight)
+            .sum()
+    }
+
+    fn final_value(&self, lead: i32) -> f32 {
+        self.weights[0] * lead as f32
+    }
+}
+
+/// The terms of the evaluation, each player one's minus player two's:
+///
+/// - `store`: seeds in the score hole
+/// - `material`: seeds in the pits
+/// - `capturable`: the most seeds a single move captures
+/// - `free_moves`: pits whose sowing ends in the score hole
+/// - `vulnerability`: seeds in pits facing an empty pit of the opponent
+/// - `sweep`: seeds the player would get if the game ended now, scaled by the
+///   share of seeds already in the score holes
+///
+/// Every term but `store` is zero once the game has ended.
+pub fn features(game_situation: &GameSituation) -> [f32; FEATURE_NUMBER] {
+    let rules = game_situation.rules();
+    let board = game_situation.board();
+    let one = side_features(game_situation, Player::One);
+    let two = side_features(game_situation, Player::Two);
+
+    let stored = board[rules.score_hole(Player::One)] + board[rules.score_hole(Player::Two)];
+    let progress = stored as f32 / rules.total_seeds() as f32;
+    let swept = match rules.sweep {
+        Sweep::ToOwner => one[1] - two[1],
+        Sweep::ToOpponent => two[1] - one[1],
+    };
+
+    let mut features = [0.0; FEATURE_NUMBER];
+    for i in 0..FEATURE_NUMBER - 1 {
+        features[i] = (one[i] - two[i]) as f32;
+    }
+    features[FEATURE_NUMBER - 1] = progress * swept as f32;
+    features
+}
+
+// the terms of `features` for `player` alone, but `sweep`
+fn side_features(game_situation: &GameSituation, player: Player) -> [i32; FEATURE_NUMBER] {
+    let rules = game_situation.rules();
+    let board = game_situation.board();
+    let first_hole = rules.first_hole(player);
+    let pits = first_hole..first_hole + rules.pit_number;
+
+    let mut side = [0; FEATURE_NUMBER];
+    side[0] = board[rules.score_hole(player)];
+    for hole_index in pits.clone() {
+        let seeds = board[hole_index];
+        if seeds == 0 {
+            continue;
+        }
+        side[1] += seeds;
+
+        let (last_hole, laps) = sowing_end(rules, hole_index, seeds);
+        if last_hole == rules.score_hole(player) {
+            side[3] += 1;
+        } else if laps == 0 &&
+            pits.contains(&last_hole) &&
+            (board[last_hole] == 0 || last_hole == hole_index) {
+            let opposite = board[rules.opposite_hole(last_hole)] + (last_hole <= hole_index) as i32;
+            if opposite > 0 || rules.empty_capture {
+                side[2] = side[2].max(opposite + 1);
+            }
+        }
+
+        if board[rules.opposite_hole(hole_index)] == 0 {
+            side[4] += seeds;
+        }
+    }
+    side
+}
+
+/// The hole where sowing `seeds` from `hole_index` ends, and how many full
+/// laps of the board the sowing makes on the way. The sowing path skips the
+/// score hole of the opponent of the pit's owner.
+pub(crate) fn sowing_end(rules: &RuleSet, hole_index: usize, seeds: i32) -> (usize, i32) {
+    let owner = if hole_index < rules.score_hole(Player::One) { Player::One } else { Player::Two };
+    let skipped = rules.score_hole(owner.opponent());
+    let cycle = rules.hole_number() as i32 - 1;
+    let laps = (seeds - 1) / cycle;
+    let mut last_hole = hole_index;
+    for _ in 0..seeds - laps * cycle {
+        last_hole = (last_hole + 1) % rules.hole_number();
+        if last_hole == skipped {
+            last_hole = (last_hole + 1) % rules.hole_number();
+        }
+    }
+    (last_hole, laps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_features() {
+        let game_situation = GameSituation::from(Player::One, &[
+            1, 0, 4, 0, 0, 1, 16,
+            4, 0, 4, 4, 4, 4, 6
+        ]);
+        // 13 and 16 are free moves and 11 captures 4 + 1 for player one; 23
+        // is a free move for player two, whose 23 and 25 face empty pits
+        let features = features(&game_situation);
+        assert_eq!(features[..5], [10.0, 6.0 - 20.0, 5.0, 2.0 - 1.0, -8.0]);
+        assert_eq!(features[5], 22.0 / 48.0 * -14.0);
+
+        let ended = GameSituation::from(Player::One, &[
+            0, 0, 0, 0, 0, 0, 20,
+            0, 0, 0, 0, 0, 0, 28
+        ]);
+        assert_eq!(super::features(&ended), [-8.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        let evaluator = FeatureEvaluator::default();
+        assert_eq!(evaluator.value_for(&ended, Player::Two), evaluator.final_value(8));
+    }
+
+    #[test]
+    fn test_store_weight_alone_is_difference() {
+        let evaluator = FeatureEvaluator::new([1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        for game_situation in [
+            GameSituation::new(Player::One),
+            GameSituation::from(Player::Two, &[
+                3, 0, 1, 7, 0, 2, 12,
+                1, 0, 9, 2, 0, 4, 7
+            ]),
+        ] {
+            assert_eq!(evaluator.value(&game_situation), Difference.value(&game_situation));
+        }
+    }
+}
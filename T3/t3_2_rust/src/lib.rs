@@ -1,259 +1,410 @@
+pub mod book;
+mod clock;
+pub mod difficulty;
+pub mod endgame;
+pub mod eval;
+pub mod mcts;
+mod oware;
+pub mod search;
+pub mod rng;
+pub mod solver;
+mod tt;
+
+use std::cell::{Cell, RefCell};
+use std::sync::OnceLock;
+
+use book::OpeningBook;
+use difficulty::{Difficulty, Level};
+use endgame::EndgameTable;
+use eval::{FeatureEvaluator, DEFAULT_WEIGHTS, FEATURE_NAMES, FEATURE_NUMBER};
+use mancala_core::{GameSituation, Player, RuleSet};
+use mcts::{Mcts, MctsConfig};
+use search::{Analysis, Search, SearchStats};
 use wasm_bindgen::prelude::wasm_bindgen;
 
-const MAX_STEP: i32 = 9;
+// most turns looked ahead, the root move included
+const MAX_STEP: i32 = 64;
+// milliseconds per decision, well under the 2000 ms limit
+const TIME_BUDGET: u32 = 300;
+// nodes `mancala_solve` searches before giving up, about a second natively
+const SOLVE_NODES: u32 = 2_000_000;
+
+/// The search families the operator can decide with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Engine {
+    /// Iterative deepening alpha-beta, with the opening book and the
+    /// evaluation weights set from JS.
+    AlphaBeta,
+    MonteCarlo(MctsConfig),
+    /// Alpha-beta weakened for people to beat, without the opening book.
+    Handicapped(Difficulty),
+}
+
+thread_local! {
+    // what the operator evaluates positions with, see `set_evaluator_weights`
+    static WEIGHTS: Cell<[f32; FEATURE_NUMBER]> = const { Cell::new(DEFAULT_WEIGHTS) };
+    // whether the operator plays from the opening book, see `set_opening_book`
+    static BOOK_ENABLED: Cell<bool> = const { Cell::new(true) };
+    // what the last alpha-beta decision took, see `search_stats`
+    static LAST_STATS: Cell<SearchStats> = Cell::new(SearchStats::default());
+    // the searches of the exports, kept so that their transposition tables
+    // are allocated once rather than on every call
+    static SEARCH: RefCell<Search<FeatureEvaluator>> = RefCell::new(Search::with_evaluator(FeatureEvaluator::default()));
+    static SOLVER: RefCell<Search> = RefCell::new(Search::new());
+}
+
+// runs `f` with the search of the operator, set to the weights from JS and
+// the endgame table, and with the positions of earlier calls forgotten
+fn with_search<T>(f: impl FnOnce(&mut Search<FeatureEvaluator>) -> T) -> T {
+    SEARCH.with(|search| {
+        let mut search = search.borrow_mut();
+        search.evaluator = FeatureEvaluator::new(WEIGHTS.with(Cell::get));
+        search.endgame = endgame_table();
+        search.stats = SearchStats::default();
+        search.clear_table();
+        f(&mut search)
+    })
+}
 
 #[wasm_bindgen]
 pub fn mancala_operator(flag: i32, status: &[i32]) -> i32 {
-    let mut max_expectation = f32::MIN;
-    let mut best_action = -1;
-
-    for i in 0..6 {
-        let mut next_situation = GameSituation::from(flag, &status);
+    operate(RuleSet::default(), Engine::AlphaBeta, flag, status, TIME_BUDGET)
+}
 
-        match next_situation.act(flag * 10 + i + 1) {
-            ILLEGAL => {
-                continue;
-            }
-            _ => {}
-        }
+/// `mancala_operator` thinking for about `ms` milliseconds instead of the
+/// default budget.
+#[wasm_bindgen]
+pub fn mancala_operator_timed(flag: i32, status: &[i32], ms: u32) -> i32 {
+    operate(RuleSet::default(), Engine::AlphaBeta, flag, status, ms)
+}
 
-        let current_expectation = decide(&next_situation, flag, MAX_STEP);
-        if current_expectation > max_expectation {
-            max_expectation = current_expectation;
-            best_action = flag * 10 + i + 1;
-        }
-    }
+/// `mancala_operator` deciding by Monte Carlo tree search: at most
+/// `playouts` playouts, UCT with the exploration constant `exploration`, and
+/// rollouts picking `"random"`, `"greedy-store"` or `"extra-turn"` moves.
+#[wasm_bindgen]
+pub fn mancala_operator_mcts(flag: i32, status: &[i32], playouts: u32, exploration: f32, rollout: &str) -> Result<i32, String> {
+    let rollout = rollout.parse()?;
+    let config = MctsConfig { playouts, exploration, rollout, ..MctsConfig::default() };
+    Ok(operate(RuleSet::default(), Engine::MonteCarlo(config), flag, status, TIME_BUDGET))
+}
 
-    return if best_action == -1 {
-        // expected not to reach
-        flag * 10 + 1
-    } else {
-        best_action
+/// `mancala_operator` at a difficulty level, `"beginner"`, `"easy"`,
+/// `"medium"`, `"hard"` or `"expert"`, within the time budget of the
+/// operator. The mistakes of the lower levels depend on the position and
+/// `seed` alone.
+#[wasm_bindgen]
+pub fn mancala_operator_level(flag: i32, status: &[i32], level: &str, seed: u32) -> Result<i32, String> {
+    let engine = match level.parse::<Level>()?.difficulty() {
+        Some(difficulty) => Engine::Handicapped(Difficulty { seed: seed as u64, ..difficulty }),
+        None => Engine::AlphaBeta,
     };
+    Ok(operate(RuleSet::default(), engine, flag, status, TIME_BUDGET))
 }
 
-fn decide(game_situation: &GameSituation, decide_for: i32, remain_step: i32) -> f32 {
-    if game_situation.ended || remain_step == 0 {
-        return value_for(&game_situation.board, decide_for);
-    }
+/// `mancala_operator` under another rule set, e.g. `"kalah(6,3)"`, with a
+/// `status` of `2 * pits + 2` holes.
+#[wasm_bindgen]
+pub fn mancala_operator_with_rules(rules: &str, flag: i32, status: &[i32]) -> Result<i32, String> {
+    let rules = rules.parse::<RuleSet>().map_err(|error| error.to_string())?;
+    Ok(operate(rules, Engine::AlphaBeta, flag, status, TIME_BUDGET))
+}
 
-    let mut values = Vec::new();
-    for i in 0..6 {
-        let mut next_situation =
-            GameSituation::from(game_situation.actor, &game_situation.board);
+/// The final score of the player to move minus the opponent's when both
+/// play perfectly from `status`, or `undefined` if that takes more than a
+/// couple of million nodes to find out.
+#[wasm_bindgen]
+pub fn mancala_solve(status: &[i32], flag: i32) -> Option<i32> {
+    solve(RuleSet::default(), status, flag, SOLVE_NODES)
+}
 
-        match next_situation.act(next_situation.actor * 10 + i + 1) {
-            ILLEGAL => {
-                continue;
-            }
-            _ => {}
-        }
-        values.push(decide(&next_situation, decide_for, remain_step - 1));
-    }
+/// `mancala_solve` under another rule set, e.g. `"kalah(4,3)"`, giving up
+/// after `max_nodes` nodes.
+#[wasm_bindgen]
+pub fn mancala_solve_with_rules(rules: &str, status: &[i32], flag: i32, max_nodes: u32) -> Result<Option<i32>, String> {
+    let rules = rules.parse::<RuleSet>().map_err(|error| error.to_string())?;
+    Ok(solve(rules, status, flag, max_nodes))
+}
 
+/// One legal move as `analyze` sees it.
+#[wasm_bindgen]
+pub struct MoveAnalysis {
+    /// The move code, as `mancala_operator` returns it.
+    pub code: i32,
+    /// What the move is worth to the player making it.
+    pub value: f32,
+    /// Nodes searched for the move.
+    pub nodes: f64,
+    principal_variation: Vec<i32>,
+}
 
-    if game_situation.actor == decide_for {
-        let mut max = f32::MIN;
-        for value in values {
-            if value > max {
-                max = value;
-            }
-        }
-        max
-    } else {
-        let mut min = f32::MAX;
-        for value in values {
-            if value < min {
-                min = value;
-            }
-        }
-        min
+#[wasm_bindgen]
+impl MoveAnalysis {
+    /// The move codes of the expected line of play, starting with this move.
+    #[wasm_bindgen(getter)]
+    pub fn principal_variation(&self) -> Vec<i32> {
+        self.principal_variation.clone()
     }
 }
 
-fn value_for(status: &[i32], decide_for: i32) -> f32 {
-    match decide_for {
-        1 => { value(status) }
-        _ => { -value(status) }
+impl From<Analysis> for MoveAnalysis {
+    fn from(analysis: Analysis) -> MoveAnalysis {
+        MoveAnalysis {
+            code: analysis.mv.code(),
+            value: analysis.value,
+            nodes: analysis.nodes as f64,
+            principal_variation: analysis.principal_variation.iter().map(|mv| mv.code()).collect(),
+        }
     }
 }
 
-// E(score) for player one
-fn value(status: &[i32]) -> f32 {
-    (status[PLAYER_1_SCORE_HOLE] - status[PLAYER_2_SCORE_HOLE]) as f32
+/// Every legal move of player `flag` on `status`, best first, searched
+/// `depth` turns deep with the weights and endgame table of the operator.
+#[wasm_bindgen]
+pub fn analyze(status: &[i32], flag: i32, depth: i32) -> Vec<MoveAnalysis> {
+    let mut game_situation = GameSituation::from(Player::from_flag(flag), status);
+    let analyses = with_search(|search| search.analyze(&mut game_situation, depth.clamp(1, MAX_STEP)));
+    analyses.into_iter().map(MoveAnalysis::from).collect()
 }
 
-const HOLE_NUMBER: usize = 14;
-
-pub struct GameSituation {
-    pub actor: i32,
-    board: [i32; HOLE_NUMBER],
-    ended: bool,
+/// The weights of the evaluation terms the operator plays with: store,
+/// material, capturable, free moves, vulnerability and sweep.
+#[wasm_bindgen]
+pub fn evaluator_weights() -> Vec<f32> {
+    WEIGHTS.with(Cell::get).to_vec()
 }
 
-const PLAYER_1_SCORE_HOLE: usize = 6;
-const PLAYER_2_SCORE_HOLE: usize = 13;
-
-
-const ENDED: i32 = 15000;
-const NOT_ENDED: i32 = 20000;
-const ILLEGAL: i32 = 30000;
+/// Sets the weights of the evaluation terms, in the order of
+/// `evaluator_weights`, for every later decision. `[1, 0, 0, 0, 0, 0]` plays
+/// with the difference of the score holes alone.
+#[wasm_bindgen]
+pub fn set_evaluator_weights(weights: &[f32]) -> Result<(), String> {
+    let weights: [f32; FEATURE_NUMBER] = weights.try_into().map_err(|_| {
+        format!("expected {} weights ({}), got {}", FEATURE_NUMBER, FEATURE_NAMES.join(", "), weights.len())
+    })?;
+    if let Some(i) = weights.iter().position(|weight| !weight.is_finite()) {
+        return Err(format!("the weight of {} is not a number", FEATURE_NAMES[i]));
+    }
+    WEIGHTS.with(|cell| cell.set(weights));
+    Ok(())
+}
 
-impl GameSituation {
-    pub fn new(first_actor: i32) -> GameSituation {
-        let mut situation = GameSituation {
-            actor: first_actor,
-            board: [4; HOLE_NUMBER],
-            ended: false,
-        };
+/// Turns the opening book of the operator on or off. It is on by default,
+/// and answers the first moves of standard Kalah without searching.
+#[wasm_bindgen]
+pub fn set_opening_book(enabled: bool) {
+    BOOK_ENABLED.with(|cell| cell.set(enabled));
+}
 
-        situation.board[PLAYER_1_SCORE_HOLE] = 0;
-        situation.board[PLAYER_2_SCORE_HOLE] = 0;
+/// What the last decision of the operator searched: nodes, leaves, cutoffs,
+/// transposition table hits, the most moves deep a line went and the
+/// milliseconds taken. All zero after a book move or a Monte Carlo decision.
+#[wasm_bindgen]
+pub fn search_stats() -> Vec<f64> {
+    let stats = LAST_STATS.with(Cell::get);
+    vec![
+        stats.nodes as f64,
+        stats.leaves as f64,
+        stats.cutoffs as f64,
+        stats.tt_hits as f64,
+        stats.max_depth as f64,
+        stats.elapsed_ms,
+    ]
+}
 
-        situation
+/// The move code `engine` picks for player `flag` on `status`, thinking for
+/// about `time_budget` milliseconds at most.
+pub fn operate(rules: RuleSet, engine: Engine, flag: i32, status: &[i32], time_budget: u32) -> i32 {
+    let player = Player::from_flag(flag);
+    let mut game_situation = GameSituation::from_with_rules(rules, player, status);
+    LAST_STATS.with(|cell| cell.set(SearchStats::default()));
+
+    let best_move = match engine {
+        Engine::AlphaBeta => {
+            let book_move = opening_book().lookup(&game_situation).filter(|_| BOOK_ENABLED.with(Cell::get));
+            book_move.or_else(|| with_search(|search| {
+                #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+                let best = search.iterative_deepening_parallel(&game_situation, MAX_STEP, time_budget as f64);
+                #[cfg(not(all(feature = "parallel", not(target_arch = "wasm32"))))]
+                let best = search.iterative_deepening(&mut game_situation, MAX_STEP, time_budget as f64);
+                LAST_STATS.with(|cell| cell.set(search.stats));
+                best.map(|(mv, _)| mv)
+            }))
+        }
+        Engine::MonteCarlo(config) => Mcts::new(config).best_move(&mut game_situation, time_budget as f64),
+        Engine::Handicapped(difficulty) => with_search(|search| {
+            let best = difficulty::choose(search, &mut game_situation, &difficulty, time_budget as f64);
+            LAST_STATS.with(|cell| cell.set(search.stats));
+            best
+        }),
+    };
+    match best_move {
+        Some(mv) => mv.code(),
+        // expected not to reach
+        None => flag * 10 + 1,
     }
+}
 
-    pub fn from(first_actor: i32, status: &[i32]) -> GameSituation {
-        let mut situation = GameSituation {
-            actor: first_actor,
-            board: [0; HOLE_NUMBER],
-            ended: false,
-        };
+fn solve(rules: RuleSet, status: &[i32], flag: i32, max_nodes: u32) -> Option<i32> {
+    let mut game_situation = GameSituation::from_with_rules(rules, Player::from_flag(flag), status);
+    SOLVER.with(|search| {
+        let mut search = search.borrow_mut();
+        search.endgame = endgame_table();
+        search.stats = SearchStats::default();
+        search.clear_table();
+        solver::solve(&mut search, &mut game_situation, max_nodes as u64).map(|solution| solution.value)
+    })
+}
 
-        for i in 0..14 {
-            situation.board[i] = status[i];
-        }
+/// The opening book the operator plays from, written by `mancala-book` in
+/// /mancala-tools.
+pub fn opening_book() -> &'static OpeningBook {
+    static BOOK: OnceLock<OpeningBook> = OnceLock::new();
+    BOOK.get_or_init(|| OpeningBook::parse(include_str!("book.txt")).unwrap_or_default())
+}
 
-        situation
-    }
+// the table `build.rs` generated for the `endgame-table` feature
+#[cfg(feature = "endgame-table")]
+fn endgame_table() -> Option<&'static EndgameTable> {
+    static TABLE: OnceLock<Option<EndgameTable>> = OnceLock::new();
+    TABLE
+        .get_or_init(|| EndgameTable::from_bytes(include_bytes!(concat!(env!("OUT_DIR"), "/endgame.bin"))))
+        .as_ref()
+}
 
-    pub fn act(&mut self, action: i32) -> i32 {
-        let actor = action / 10;
-        let hole_index = (action % 10 + (actor - 1) * 7 - 1) as usize;
+#[cfg(not(feature = "endgame-table"))]
+fn endgame_table() -> Option<&'static EndgameTable> {
+    None
+}
 
-        // the game should not be ended
-        if self.ended {
-            return ILLEGAL;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mancala_core::Move;
+
+    #[test]
+    fn test_set_evaluator_weights() {
+        assert_eq!(evaluator_weights(), DEFAULT_WEIGHTS);
+        assert!(set_evaluator_weights(&[1.0, 0.0]).is_err());
+        assert!(set_evaluator_weights(&[1.0, 0.0, f32::NAN, 0.0, 0.0, 0.0]).is_err());
+        assert_eq!(evaluator_weights(), DEFAULT_WEIGHTS);
+        let status = [4, 0, 1, 6, 6, 6, 1, 4, 4, 4, 4, 4, 4, 0];
+        analyze(&status, 2, 4);
+
+        set_evaluator_weights(&[1.0, 0.0, 0.0, 0.0, 0.0, 0.0]).unwrap();
+        assert_eq!(evaluator_weights(), [1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(mancala_operator_timed(1, &[4, 4, 4, 4, 4, 4, 0, 4, 4, 4, 4, 4, 4, 0], 50) / 10, 1);
+        // the search kept from call to call forgets the values of the old weights
+        let values = analyze(&status, 2, 4).iter().map(|analysis| analysis.value).collect::<Vec<_>>();
+        let analyses = Search::new().analyze(&mut GameSituation::from(Player::Two, &status), 4);
+        assert_eq!(values, analyses.iter().map(|analysis| analysis.value).collect::<Vec<_>>());
+    }
 
-        // the actor should be correct
-        if actor != self.actor {
-            return ILLEGAL;
-        }
+    #[test]
+    fn test_solve() {
+        let status = [0, 0, 2, 0, 1, 0, 21, 0, 3, 0, 1, 0, 2, 18];
+        let (_, value) = Search::new().best_move(&mut GameSituation::from(Player::One, &status), 64).unwrap();
+        assert_eq!(mancala_solve(&status, 1), Some(value as i32));
+        assert_eq!(mancala_solve(&[4, 4, 4, 4, 4, 4, 0, 4, 4, 4, 4, 4, 4, 0], 1), None);
+        assert_eq!(mancala_solve_with_rules("kalah(2,2)", &[2, 2, 0, 2, 2, 0], 1, 1000), Ok(Some(-2)));
+        assert!(mancala_solve_with_rules("oware", &status, 1, 1000).is_err());
+    }
 
-        // the hole should not be empty
-        if self.board[hole_index] == 0 {
-            return ILLEGAL;
+    #[test]
+    fn test_analyze() {
+        // 11 ends in the empty pit 3 and captures, well ahead of the four
+        // other moves
+        let status = [2, 3, 0, 5, 1, 4, 10, 3, 0, 6, 2, 5, 1, 6];
+        let analyses = analyze(&status, 1, 4);
+        assert_eq!(analyses.len(), 5);
+        assert_eq!(analyses[0].code, 11);
+        assert!(analyses[0].value > analyses[1].value + 4.0);
+
+        // every move of a quiet position, ranked by the value of the
+        // position it leads to
+        let status = [4, 0, 1, 6, 6, 6, 1, 4, 4, 4, 4, 4, 4, 0];
+        let analyses = analyze(&status, 2, 3);
+        let mut codes = analyses.iter().map(|analysis| analysis.code).collect::<Vec<_>>();
+        codes.sort();
+        assert_eq!(codes, [21, 22, 23, 24, 25, 26]);
+        for pair in analyses.windows(2) {
+            assert!(pair[0].value >= pair[1].value);
         }
-
-        // get all pieces form this hole
-        let mut mancala_pieces = self.board[hole_index];
-        self.board[hole_index] = 0;
-
-        // sow!
-        let mut current_hole = Self::next_hole(hole_index);
-        while mancala_pieces > 0 {
-            // if opponent's score hole, skip it
-            if self.is_opponent_score_hole(current_hole) {
-                current_hole = Self::next_hole(current_hole);
-            }
-            // else, sow one piece in current hole
-            self.board[current_hole] += 1;
-            mancala_pieces -= 1;
-
-            // if this is the last piece
-            if mancala_pieces == 0 {
-                // move again
-                if self.can_move_again(current_hole) {
-                    self.actor = 3 - self.actor;
-                }
-                // critical hit
-                self.try_critical_hit(current_hole);
+        for analysis in &analyses {
+            assert!(analysis.nodes > 0.0);
+            let mut game_situation = GameSituation::from(Player::Two, &status);
+            for &code in &analysis.principal_variation() {
+                game_situation.act(Move::try_from(code).unwrap()).unwrap();
             }
-            current_hole = Self::next_hole(current_hole);
-        }
-
-        // change actor
-        self.actor = 3 - self.actor;
 
-        if self.try_end() {
-            ENDED
-        } else {
-            NOT_ENDED
+            let mut game_situation = GameSituation::from(Player::Two, &status);
+            game_situation.act(Move::try_from(analysis.code).unwrap()).unwrap();
+            let actor = game_situation.actor();
+            let replies = analyze(game_situation.board(), actor.number(), if actor == Player::Two { 3 } else { 2 });
+            let value = if actor == Player::Two { replies[0].value } else { -replies[0].value };
+            assert_eq!(analysis.value, value, "{}", analysis.code);
         }
     }
 
-    fn next_hole(hole_index: usize) -> usize {
-        (hole_index + 1usize) % HOLE_NUMBER
+    #[test]
+    fn test_search_stats() {
+        let status = [3, 0, 1, 7, 0, 2, 12, 1, 0, 9, 2, 0, 4, 7];
+        mancala_operator_timed(1, &status, 50);
+        let stats = search_stats();
+        assert_eq!(stats.len(), 6);
+        assert!(stats[0] > 0.0 && stats[1] > 0.0);
+
+        // the counts of the last decision alone, growing with its depth
+        let status = [4, 0, 1, 6, 6, 6, 1, 4, 4, 4, 4, 4, 4, 0];
+        mancala_operator_level(2, &status, "easy", 1).unwrap();
+        let easy = search_stats();
+        mancala_operator_level(2, &status, "medium", 1).unwrap();
+        let medium = search_stats();
+        assert!(easy[1] < easy[0] && easy[0] < medium[0]);
+        assert!(easy[2] < medium[2] && easy[3] < medium[3]);
+        assert!(easy[4] >= 2.0 && medium[4] >= 4.0 && easy[4] < medium[4]);
+        mancala_operator_level(2, &status, "easy", 1).unwrap();
+        assert_eq!(search_stats()[..5], easy[..5]);
+
+        // the book answers the first move without searching
+        mancala_operator_timed(1, &[4, 4, 4, 4, 4, 4, 0, 4, 4, 4, 4, 4, 4, 0], 50);
+        assert_eq!(search_stats(), [0.0; 6]);
     }
 
-    fn is_opponent_score_hole(&self, hole_index: usize) -> bool {
-        self.actor == 1 && hole_index == PLAYER_2_SCORE_HOLE ||
-            self.actor == 2 && hole_index == PLAYER_1_SCORE_HOLE
-    }
-
-    fn can_move_again(&self, hole_index: usize) -> bool {
-        self.actor == 1 && hole_index == PLAYER_1_SCORE_HOLE ||
-            self.actor == 2 && hole_index == PLAYER_2_SCORE_HOLE
-    }
-
-    fn try_critical_hit(&mut self, hole_index: usize) {
-        if self.is_my_six_hole(hole_index) &&
-            self.board[hole_index] == 1 &&
-            self.board[self.opposite_hole(hole_index)] > 0 {
-            self.board[self.my_score_hole()] += 1 + self.board[self.opposite_hole(hole_index)];
-            self.board[hole_index] = 0;
-            self.board[self.opposite_hole(hole_index)] = 0;
-        }
+    #[test]
+    fn test_mancala_operator_level() {
+        // 11 captures the 10 seeds of 25 and wins; anything else loses them
+        let status = [1, 0, 0, 1, 1, 0, 19, 1, 1, 1, 1, 10, 1, 11];
+        assert_eq!(mancala_operator_level(1, &status, "hard", 1), Ok(11));
+        assert_eq!(mancala_operator_level(1, &status, "expert", 1), Ok(11));
+        let beginner = mancala_operator_level(1, &status, "beginner", 7);
+        assert_eq!(mancala_operator_level(1, &status, "beginner", 7), beginner);
+        assert!(mancala_operator_level(1, &status, "impossible", 1).is_err());
     }
 
-    fn my_score_hole(&self) -> usize {
-        if self.actor == 1 {
-            PLAYER_1_SCORE_HOLE
-        } else {
-            PLAYER_2_SCORE_HOLE
-        }
+    #[test]
+    fn test_mancala_operator_mcts() {
+        // 15 captures the 8 seeds of 21
+        let status = [0, 0, 2, 0, 1, 0, 18, 8, 1, 2, 1, 2, 2, 11];
+        assert_eq!(mancala_operator_mcts(1, &status, 2000, 1.4, "greedy-store"), Ok(15));
+        assert!(mancala_operator_mcts(1, &status, 2000, 1.4, "greedy").is_err());
     }
 
-    fn is_my_six_hole(&self, hole_index: usize) -> bool {
-        ((self.actor - 1) * 7) as usize <= hole_index &&
-            hole_index <= ((self.actor - 1) * 7 + 6) as usize
-    }
+    #[test]
+    fn test_opening_book() {
+        assert!(OpeningBook::parse(include_str!("book.txt")).is_ok());
+        let status = [4, 4, 4, 4, 4, 4, 0, 4, 4, 4, 4, 4, 4, 0];
+        let book_move = opening_book().lookup(&GameSituation::from(Player::One, &status)).unwrap();
+        assert_eq!(mancala_operator(1, &status), book_move.code());
 
-    fn opposite_hole(&self, hole_index: usize) -> usize {
-        assert!(
-            hole_index != PLAYER_1_SCORE_HOLE &&
-                hole_index != PLAYER_2_SCORE_HOLE
-        );
-        12 - hole_index
+        set_opening_book(false);
+        assert_eq!(mancala_operator_timed(1, &status, 50) / 10, 1);
+        set_opening_book(true);
     }
 
-    fn try_end(&mut self) -> bool {
-        let mut piece_number = 0;
-        for i in 0..6 {
-            piece_number += self.board[i];
-        }
-        if piece_number == 0 {
-            for i in 7..13 {
-                self.board[PLAYER_2_SCORE_HOLE] += self.board[i];
-                self.board[i] = 0;
-                self.ended = true;
-            }
-        }
-        piece_number = 0;
-        for i in 7..13 {
-            piece_number += self.board[i];
-        }
-        if piece_number == 0 {
-            for i in 0..6 {
-                self.board[PLAYER_1_SCORE_HOLE] += self.board[i];
-                self.board[i] = 0;
-                self.ended = true;
-            }
-        }
-        self.ended
+    #[cfg(feature = "endgame-table")]
+    #[test]
+    fn test_embedded_endgame_table() {
+        let game_situation = GameSituation::from(Player::One, &[0, 0, 0, 0, 1, 1, 20, 0, 0, 0, 0, 1, 0, 25]);
+        assert_eq!(endgame_table().unwrap().probe(&game_situation), Some(1));
     }
 }
-
-#[cfg(test)]
-mod tests {}
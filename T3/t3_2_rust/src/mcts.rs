@@ -0,0 +1,294 @@
+use std::fmt;
+use std::str::FromStr;
+
+use mancala_core::{GameSituation, Move, Player};
+
+use crate::clock::now_ms;
+use crate::eval::sowing_end;
+use crate::rng::Rng;
+use crate::search::ordered_moves;
+
+// how many playouts to run between two looks at the clock
+const CLOCK_INTERVAL: u32 = 64;
+
+/// How the moves of a playout past the tree are picked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rollout {
+    /// Any legal move.
+    Random,
+    /// A move that adds the most seeds to the mover's score hole, captures
+    /// included.
+    GreedyStore,
+    /// A move ending in the mover's score hole if there is one, else any.
+    ExtraTurn,
+}
+
+impl FromStr for Rollout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Rollout, String> {
+        match s {
+            "random" => Ok(Rollout::Random),
+            "greedy-store" => Ok(Rollout::GreedyStore),
+            "extra-turn" => Ok(Rollout::ExtraTurn),
+            _ => Err(format!("unknown rollout policy `{}`", s)),
+        }
+    }
+}
+
+impl fmt::Display for Rollout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Rollout::Random => "random",
+            Rollout::GreedyStore => "greedy-store",
+            Rollout::ExtraTurn => "extra-turn",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MctsConfig {
+    /// Most playouts per decision.
+    pub playouts: u32,
+    /// The exploration constant of UCT.
+    pub exploration: f32,
+    pub rollout: Rollout,
+    pub seed: u64,
+}
+
+impl Default for MctsConfig {
+    fn default() -> MctsConfig {
+        MctsConfig {
+            playouts: 20000,
+            exploration: std::f32::consts::SQRT_2,
+            rollout: Rollout::ExtraTurn,
+            seed: 1,
+        }
+    }
+}
+
+struct Node {
+    // the move leading here, and who played it
+    mv: Option<Move>,
+    mover: Player,
+    children: Vec<usize>,
+    untried: Vec<Move>,
+    visits: u32,
+    // 1 per win, 0.5 per draw of `mover` in the playouts through here
+    reward: f32,
+}
+
+/// Monte Carlo tree search with UCT, playing moves on `GameSituation` and
+/// taking them back after every playout.
+pub struct Mcts {
+    pub config: MctsConfig,
+    /// Playouts of the last decision.
+    pub playouts: u32,
+    rng: Rng,
+    nodes: Vec<Node>,
+}
+
+impl Mcts {
+    pub fn new(config: MctsConfig) -> Mcts {
+        Mcts { config, playouts: 0, rng: Rng::new(config.seed), nodes: Vec::new() }
+    }
+
+    /// The most played move after `config.playouts` playouts or
+    /// `time_budget` milliseconds, or `None` if there is no legal move.
+    pub fn best_move(&mut self, game_situation: &mut GameSituation, time_budget: f64) -> Option<Move> {
+        let deadline = now_ms() + time_budget;
+        self.nodes.clear();
+        self.nodes.push(Node {
+            mv: None,
+            mover: game_situation.actor().opponent(),
+            children: Vec::new(),
+            untried: ordered_moves(game_situation, None).collect(),
+            visits: 0,
+            reward: 0.0,
+        });
+
+        self.playouts = 0;
+        // the game has ended, or the actor has no seeds to sow
+        if self.nodes[0].untried.is_empty() {
+            return None;
+        }
+        while self.playouts < self.config.playouts {
+            if self.playouts.is_multiple_of(CLOCK_INTERVAL) && self.playouts > 0 && now_ms() >= deadline {
+                break;
+            }
+            self.playout(game_situation);
+            self.playouts += 1;
+        }
+
+        let root = &self.nodes[0];
+        root.children
+            .iter()
+            .max_by_key(|&&child| self.nodes[child].visits)
+            .and_then(|&child| self.nodes[child].mv)
+    }
+
+    // one round of selection, expansion, rollout and backpropagation
+    fn playout(&mut self, game_situation: &mut GameSituation) {
+        let mut path = vec![0];
+        let mut node = 0;
+        while self.nodes[node].untried.is_empty() && !self.nodes[node].children.is_empty() {
+            node = self.select(node);
+            game_situation.play(self.nodes[node].mv.unwrap()).unwrap();
+            path.push(node);
+        }
+
+        if !self.nodes[node].untried.is_empty() {
+            let untried = &mut self.nodes[node].untried;
+            let mv = untried.swap_remove(self.rng.below(untried.len()));
+            let mover = game_situation.actor();
+            game_situation.play(mv).unwrap();
+            let child = self.nodes.len();
+            self.nodes.push(Node {
+                mv: Some(mv),
+                mover,
+                children: Vec::new(),
+                untried: ordered_moves(game_situation, None).collect(),
+                visits: 0,
+                reward: 0.0,
+            });
+            self.nodes[node].children.push(child);
+            path.push(child);
+        }
+
+        let mut plies = path.len() - 1;
+        while !game_situation.ended() {
+            let mv = self.rollout_move(game_situation);
+            game_situation.play(mv).unwrap();
+            plies += 1;
+        }
+
+        let lead = game_situation.score(Player::One) - game_situation.score(Player::Two);
+        for &node in &path {
+            let node = &mut self.nodes[node];
+            let lead = if node.mover == Player::One { lead } else { -lead };
+            node.visits += 1;
+            node.reward += if lead > 0 { 1.0 } else if lead == 0 { 0.5 } else { 0.0 };
+        }
+        for _ in 0..plies {
+            game_situation.unplay();
+        }
+    }
+
+    // the child of `node` with the highest upper confidence bound
+    fn select(&self, node: usize) -> usize {
+        let log_visits = (self.nodes[node].visits as f32).ln();
+        let bound = |child: usize| {
+            let child = &self.nodes[child];
+            let visits = child.visits as f32;
+            child.reward / visits + self.config.exploration * (log_visits / visits).sqrt()
+        };
+        let children = &self.nodes[node].children;
+        children.iter().copied().max_by(|&a, &b| bound(a).total_cmp(&bound(b))).unwrap()
+    }
+
+    fn rollout_move(&mut self, game_situation: &mut GameSituation) -> Move {
+        let moves = ordered_moves(game_situation, None).collect::<Vec<_>>();
+        match self.config.rollout {
+            Rollout::Random => moves[self.rng.below(moves.len())],
+            Rollout::GreedyStore => {
+                let actor = game_situation.actor();
+                let gains = moves
+                    .iter()
+                    .map(|&mv| {
+                        let before = game_situation.score(actor);
+                        game_situation.play(mv).unwrap();
+                        let gain = game_situation.score(actor) - before;
+                        game_situation.unplay();
+                        gain
+                    })
+                    .collect::<Vec<_>>();
+                let most = *gains.iter().max().unwrap();
+                let greedy = moves.iter().zip(&gains).filter(|&(_, &gain)| gain == most).map(|(&mv, _)| mv);
+                let greedy = greedy.collect::<Vec<_>>();
+                greedy[self.rng.below(greedy.len())]
+            }
+            Rollout::ExtraTurn => {
+                let rules = game_situation.rules();
+                let board = game_situation.board();
+                let free = moves
+                    .iter()
+                    .copied()
+                    .filter(|&mv| {
+                        let hole_index = rules.hole_index(mv);
+                        sowing_end(rules, hole_index, board[hole_index]).0 == rules.score_hole(mv.player)
+                    })
+                    .collect::<Vec<_>>();
+                if free.is_empty() {
+                    moves[self.rng.below(moves.len())]
+                } else {
+                    free[self.rng.below(free.len())]
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rollout_names() {
+        for rollout in [Rollout::Random, Rollout::GreedyStore, Rollout::ExtraTurn] {
+            assert_eq!(rollout.to_string().parse(), Ok(rollout));
+        }
+        assert!("greedy".parse::<Rollout>().is_err());
+    }
+
+    #[test]
+    fn test_best_move() {
+        // 15 ends in the empty pit 6 and captures the 8 seeds of 21, 13
+        // leaves them to player two
+        let mut game_situation = GameSituation::from(Player::One, &[
+            0, 0, 2, 0, 1, 0, 18,
+            8, 1, 2, 1, 2, 2, 11
+        ]);
+        let before = game_situation.clone();
+        for rollout in [Rollout::Random, Rollout::GreedyStore, Rollout::ExtraTurn] {
+            let mut mcts = Mcts::new(MctsConfig { playouts: 2000, rollout, ..MctsConfig::default() });
+            assert_eq!(mcts.best_move(&mut game_situation, f64::INFINITY), Some(Move::new(Player::One, 5)));
+            assert_eq!(mcts.playouts, 2000);
+            assert_eq!(game_situation, before);
+        }
+
+        let mut mcts = Mcts::new(MctsConfig::default());
+        assert!(mcts.best_move(&mut GameSituation::new(Player::Two), 0.0).is_some());
+        assert!(mcts.playouts < MctsConfig::default().playouts);
+
+        // no playouts once the game has ended
+        let mut game_situation = GameSituation::from(Player::One, &[
+            0, 0, 0, 0, 0, 1, 20,
+            1, 0, 3, 0, 2, 0, 21
+        ]);
+        game_situation.act(Move::new(Player::One, 6)).unwrap();
+        assert!(game_situation.ended());
+        assert_eq!(mcts.best_move(&mut game_situation, f64::INFINITY), None);
+        assert_eq!(mcts.playouts, 0);
+    }
+
+    #[test]
+    fn test_rollout_policies() {
+        // the same seed and playouts, spread over the root moves by how the
+        // playouts past the tree are played
+        let visits = [Rollout::Random, Rollout::GreedyStore, Rollout::ExtraTurn].map(|rollout| {
+            let mut mcts = Mcts::new(MctsConfig { playouts: 300, rollout, ..MctsConfig::default() });
+            mcts.best_move(&mut GameSituation::new(Player::One), f64::INFINITY);
+            let mut visits = mcts.nodes[0]
+                .children
+                .iter()
+                .map(|&child| (mcts.nodes[child].mv.unwrap().code(), mcts.nodes[child].visits))
+                .collect::<Vec<_>>();
+            visits.sort();
+            visits
+        });
+        assert!(visits.iter().all(|visits| visits.len() == 6));
+        assert_ne!(visits[0], visits[1]);
+        assert_ne!(visits[0], visits[2]);
+        assert_ne!(visits[1], visits[2]);
+    }
+}
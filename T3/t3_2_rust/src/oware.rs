@@ -0,0 +1,115 @@
+use std::cmp::Reverse;
+
+use mancala_core::{Move, OwareSituation, Player, PIT_NUMBER, TOTAL_SEEDS};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+// plies searched ahead, alternating as Oware has no free moves
+const MAX_DEPTH: i32 = 12;
+
+/// `mancala_operator` for a game of Oware.
+#[wasm_bindgen]
+pub fn oware_operator(flag: i32, status: &[i32]) -> i32 {
+    let oware_situation = OwareSituation::from(Player::from_flag(flag), status);
+    let mut alpha = -TOTAL_SEEDS;
+    let mut best_action = -1;
+
+    for (mv, next_situation) in children(&oware_situation) {
+        let value = -negamax(&next_situation, MAX_DEPTH - 1, -TOTAL_SEEDS, -alpha);
+        if best_action == -1 || value > alpha {
+            alpha = value;
+            best_action = mv.code();
+        }
+    }
+
+    if best_action == -1 {
+        // expected not to reach
+        flag * 10 + 1
+    } else {
+        best_action
+    }
+}
+
+// the legal moves with the situations they lead to, the biggest captures first
+fn children(oware_situation: &OwareSituation) -> Vec<(Move, OwareSituation)> {
+    let actor = oware_situation.actor();
+    let mut children = (1..=PIT_NUMBER)
+        .filter_map(|pit| {
+            let mv = Move::new(actor, pit);
+            let mut next_situation = oware_situation.clone();
+            next_situation.act(mv).ok().map(|_| (mv, next_situation))
+        })
+        .collect::<Vec<_>>();
+    children.sort_by_key(|(_, next_situation)| Reverse(next_situation.score(actor)));
+    children
+}
+
+// the lead of the actor with `remain_step` plies of alpha-beta search
+fn negamax(oware_situation: &OwareSituation, remain_step: i32, mut alpha: i32, beta: i32) -> i32 {
+    let actor = oware_situation.actor();
+    let lead = oware_situation.score(actor) - oware_situation.score(actor.opponent());
+    if oware_situation.ended() || remain_step == 0 {
+        return lead;
+    }
+
+    let mut best = None;
+    for (_, next_situation) in children(oware_situation) {
+        let value = -negamax(&next_situation, remain_step - 1, -beta, -alpha);
+        best = Some(best.map_or(value, |best: i32| best.max(value)));
+        alpha = alpha.max(value);
+        if alpha >= beta {
+            break;
+        }
+    }
+    best.unwrap_or(lead)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the full-width minimax this search replaces
+    fn minimax(oware_situation: &OwareSituation, decide_for: Player, remain_step: i32) -> i32 {
+        if oware_situation.ended() || remain_step == 0 {
+            return oware_situation.score(decide_for) - oware_situation.score(decide_for.opponent());
+        }
+        let values = children(oware_situation)
+            .into_iter()
+            .map(|(_, next_situation)| minimax(&next_situation, decide_for, remain_step - 1));
+        if oware_situation.actor() == decide_for {
+            values.max().unwrap()
+        } else {
+            values.min().unwrap()
+        }
+    }
+
+    #[test]
+    fn test_matches_minimax() {
+        let mut oware_situation = OwareSituation::new(Player::One);
+        for code in [13, 24, 16, 21, 12, 25, 15, 26] {
+            let actor = oware_situation.actor();
+            for depth in 1..=5 {
+                assert_eq!(
+                    negamax(&oware_situation, depth, -TOTAL_SEEDS, TOTAL_SEEDS),
+                    minimax(&oware_situation, actor, depth)
+                );
+            }
+            oware_situation.act(Move::try_from(code).unwrap()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_takes_chained_capture() {
+        assert_eq!(oware_operator(1, &[
+            1, 0, 0, 0, 0, 3, 0,
+            1, 2, 1, 4, 4, 4, 0
+        ]), 16);
+    }
+
+    #[test]
+    fn test_feeds_starving_opponent() {
+        assert_eq!(oware_operator(1, &[
+            1, 0, 0, 0, 0, 1, 10,
+            0, 0, 0, 0, 0, 0, 10
+        ]), 16);
+    }
+}
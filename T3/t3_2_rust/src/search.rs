@@ -0,0 +1,884 @@
+use mancala_core::{GameSituation, Move, Player, PIT_NUMBER};
+
+use crate::clock::now_ms;
+use crate::endgame::EndgameTable;
+use crate::eval::{sowing_end, Difference, Evaluator};
+use crate::tt::{self, Bound, TranspositionTable};
+
+/// Width of the null windows of the principal variation search. Any width is
+/// sound, but values of fractional evaluators that fall inside the window
+/// cost a second search.
+const NULL_WINDOW: f32 = 1.0 / 64.0;
+
+// how many nodes to search between two looks at the clock
+const CLOCK_INTERVAL: u64 = 1024;
+
+// the transposition table holds 2^TT_BITS entries
+const TT_BITS: u32 = 18;
+
+// the depth stored for values that reach the end of the game on every line
+const EXHAUSTIVE: i32 = i32::MAX;
+
+// the longest principal variation `analyze` reads from the table
+const MAX_VARIATION: usize = 64;
+
+/// What `Search::analyze` found out about one root move.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis {
+    pub mv: Move,
+    /// The value of the move for the player making it.
+    pub value: f32,
+    /// The expected line of play, starting with the move itself.
+    pub principal_variation: Vec<Move>,
+    /// Nodes searched for this move.
+    pub nodes: u64,
+}
+
+/// What a search has done, summed over its calls.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SearchStats {
+    pub nodes: u64,
+    /// Nodes valued without looking further: ends of the game, endgame table
+    /// hits and the horizon.
+    pub leaves: u64,
+    /// Moves that refuted their position, leaving the moves after them
+    /// unsearched.
+    pub cutoffs: u64,
+    /// Nodes found in the transposition table.
+    pub tt_hits: u64,
+    /// Most moves played below the root on one line, free moves included.
+    pub max_depth: u32,
+    /// Milliseconds spent searching.
+    pub elapsed_ms: f64,
+}
+
+impl SearchStats {
+    /// Adds the counts of a search that ran alongside, leaving the elapsed
+    /// time as is.
+    pub fn merge(&mut self, other: &SearchStats) {
+        self.nodes += other.nodes;
+        self.leaves += other.leaves;
+        self.cutoffs += other.cutoffs;
+        self.tt_hits += other.tt_hits;
+        self.max_depth = self.max_depth.max(other.max_depth);
+    }
+}
+
+/// Alpha-beta negamax over `GameSituation`, playing and taking back moves in
+/// place. Values are always from the point of view of the player to move.
+pub struct Search<E = Difference> {
+    pub evaluator: E,
+    /// Exact values of the positions with few seeds left, if any.
+    pub endgame: Option<&'static EndgameTable>,
+    pub stats: SearchStats,
+    /// Nodes after which the search gives up, as if out of time.
+    pub max_nodes: u64,
+    /// The deepest iteration `iterative_deepening` finished.
+    pub completed_depth: i32,
+    deadline: f64,
+    aborted: bool,
+    // some leaf of the last iteration was cut off by the depth limit
+    hit_horizon: bool,
+    // moves played below the root
+    ply: u32,
+    tt: TranspositionTable,
+}
+
+impl Search {
+    /// A search valuing positions by the difference of the score holes.
+    pub fn new() -> Search {
+        Search::with_evaluator(Difference)
+    }
+}
+
+impl<E: Evaluator> Search<E> {
+    pub fn with_evaluator(evaluator: E) -> Search<E> {
+        Search {
+            evaluator,
+            endgame: None,
+            stats: SearchStats::default(),
+            max_nodes: u64::MAX,
+            completed_depth: 0,
+            deadline: f64::INFINITY,
+            aborted: false,
+            hit_horizon: false,
+            ply: 0,
+            tt: TranspositionTable::new(TT_BITS),
+        }
+    }
+
+    /// The best move for the actor of `game_situation` looking `depth` turns
+    /// ahead, with its value, or `None` if there is no legal move. A turn is
+    /// every sowing of one player, the free moves included.
+    pub fn best_move(&mut self, game_situation: &mut GameSituation, depth: i32) -> Option<(Move, f32)> {
+        self.root(game_situation, depth, f32::MIN, f32::MAX, None)
+    }
+
+    /// `best_move` by MTD(f): null-window searches closing in on the value
+    /// from `guess`, which the transposition table keeps cheap. Values must
+    /// be whole numbers, as those of `Difference` are. `None` if there is no
+    /// legal move or the search ran out of `max_nodes`.
+    pub fn mtdf(&mut self, game_situation: &mut GameSituation, depth: i32, guess: f32) -> Option<(Move, f32)> {
+        let (mut lower, mut upper) = (f32::MIN, f32::MAX);
+        let mut value = guess;
+        let mut best = None;
+        while lower < upper {
+            let beta = if value == lower { value + 1.0 } else { value };
+            let Some((mv, bound)) = self.root(game_situation, depth, beta - 1.0, beta, best.map(|(mv, _)| mv)) else {
+                self.aborted = false;
+                return None;
+            };
+            value = bound;
+            if value < beta {
+                upper = value;
+            } else {
+                lower = value;
+                best = Some((mv, value));
+            }
+        }
+        best.map(|(mv, _)| (mv, value))
+    }
+
+    /// Every legal move of `game_situation` searched `depth` turns deep with
+    /// a full window, so that each gets its exact value rather than a bound,
+    /// best first.
+    pub fn analyze(&mut self, game_situation: &mut GameSituation, depth: i32) -> Vec<Analysis> {
+        let start = now_ms();
+        let actor = game_situation.actor();
+        let moves = ordered_moves(game_situation, None).collect::<Vec<_>>();
+        let mut analyses = moves
+            .into_iter()
+            .map(|mv| {
+                let nodes = self.stats.nodes;
+                let value = self.child_value(game_situation, mv, actor, depth, f32::MIN, f32::MAX, true);
+                Analysis {
+                    mv,
+                    value,
+                    principal_variation: self.principal_variation(game_situation, mv),
+                    nodes: self.stats.nodes - nodes,
+                }
+            })
+            .collect::<Vec<_>>();
+        analyses.sort_by(|a, b| b.value.total_cmp(&a.value));
+        self.stats.elapsed_ms += now_ms() - start;
+        analyses
+    }
+
+    /// `analyze` one turn deeper at a time until `max_depth`, the end of the
+    /// game tree or `time_budget` milliseconds, returning the analyses of
+    /// the deepest search that finished.
+    pub fn analyze_iteratively(
+        &mut self,
+        game_situation: &mut GameSituation,
+        max_depth: i32,
+        time_budget: f64,
+    ) -> Vec<Analysis> {
+        let start = now_ms();
+        self.deadline = start + time_budget;
+        self.completed_depth = 0;
+        self.aborted = false;
+        self.tt.new_search();
+        let mut analyses = Vec::new();
+
+        for depth in 1..=max_depth {
+            self.hit_horizon = false;
+            let deeper = self.analyze(game_situation, depth);
+            if self.aborted {
+                break;
+            }
+            analyses = deeper;
+            self.completed_depth = depth;
+            if !self.hit_horizon || now_ms() >= start + time_budget / 2.0 {
+                break;
+            }
+        }
+
+        self.deadline = f64::INFINITY;
+        self.aborted = false;
+        analyses
+    }
+
+    // `mv` and the best moves the table remembers after it
+    fn principal_variation(&self, game_situation: &mut GameSituation, mv: Move) -> Vec<Move> {
+        let mut variation = vec![mv];
+        game_situation.play(mv).expect("ordered_moves only yields legal moves");
+        while variation.len() < MAX_VARIATION && !game_situation.ended() {
+            let Some(next) = self.tt.probe(tt::hash(game_situation)).and_then(|entry| entry.best_move) else {
+                break;
+            };
+            if game_situation.play(next).is_err() {
+                break;
+            }
+            variation.push(next);
+        }
+        for _ in &variation {
+            game_situation.unplay();
+        }
+        variation
+    }
+
+    /// Forgets the positions searched so far, without reallocating the
+    /// transposition table.
+    pub fn clear_table(&mut self) {
+        self.tt.new_search();
+    }
+
+    /// Makes the transposition table hold 2^`bits` entries, dropping them all.
+    pub fn resize_table(&mut self, bits: u32) {
+        self.tt = TranspositionTable::new(bits);
+    }
+
+    /// Searches one turn deeper at a time until `max_depth`, the end of
+    /// the game tree or `time_budget` milliseconds, and returns the best move
+    /// of the last iteration that finished.
+    pub fn iterative_deepening(
+        &mut self,
+        game_situation: &mut GameSituation,
+        max_depth: i32,
+        time_budget: f64,
+    ) -> Option<(Move, f32)> {
+        let start = now_ms();
+        self.deadline = start + time_budget;
+        self.completed_depth = 0;
+        self.aborted = false;
+        self.hit_horizon = false;
+        self.tt.new_search();
+        let mut best = None;
+
+        for depth in 1..=max_depth {
+            self.hit_horizon = false;
+            let result = self.root(game_situation, depth, f32::MIN, f32::MAX, best.map(|(mv, _)| mv));
+            if self.aborted {
+                break;
+            }
+            best = result;
+            self.completed_depth = depth;
+            // the next iteration would hardly finish in the time left
+            if !self.hit_horizon || now_ms() >= start + time_budget / 2.0 {
+                break;
+            }
+        }
+
+        self.deadline = f64::INFINITY;
+        self.aborted = false;
+        best
+    }
+
+    // searches the root moves within (alpha, beta), `first` before the
+    // others, and returns the best with its value or a bound on it
+    fn root(
+        &mut self,
+        game_situation: &mut GameSituation,
+        depth: i32,
+        mut alpha: f32,
+        beta: f32,
+        first: Option<Move>,
+    ) -> Option<(Move, f32)> {
+        let start = now_ms();
+        let actor = game_situation.actor();
+        let mut best: Option<(Move, f32)> = None;
+
+        for mv in ordered_moves(game_situation, first) {
+            let value = self.child_value(game_situation, mv, actor, depth, alpha, beta, best.is_none());
+            if self.aborted {
+                best = None;
+                break;
+            }
+            if best.is_none_or(|(_, best_value)| value > best_value) {
+                best = Some((mv, value));
+            }
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                self.stats.cutoffs += 1;
+                break;
+            }
+        }
+        self.stats.elapsed_ms += now_ms() - start;
+        best
+    }
+
+    fn negamax(&mut self, game_situation: &mut GameSituation, depth: i32, mut alpha: f32, mut beta: f32) -> f32 {
+        self.stats.nodes += 1;
+        if self.stats.nodes > self.max_nodes {
+            self.aborted = true;
+        }
+        // the first iteration always finishes, so there is a move to play
+        if self.stats.nodes.is_multiple_of(CLOCK_INTERVAL) && self.completed_depth > 0 && now_ms() >= self.deadline {
+            self.aborted = true;
+        }
+        if self.aborted {
+            return 0.0;
+        }
+        self.stats.max_depth = self.stats.max_depth.max(self.ply);
+
+        let actor = game_situation.actor();
+        if game_situation.ended() {
+            self.stats.leaves += 1;
+            return self.evaluator.value_for(game_situation, actor);
+        }
+        // perfect play from here on is known
+        if let Some(gain) = self.endgame.and_then(|table| table.probe(game_situation)) {
+            self.stats.leaves += 1;
+            let lead = game_situation.score(actor) - game_situation.score(actor.opponent()) + gain;
+            return self.evaluator.final_value(lead);
+        }
+        if depth == 0 {
+            self.stats.leaves += 1;
+            self.hit_horizon = true;
+            return self.evaluator.value_for(game_situation, actor);
+        }
+
+        let key = tt::hash(game_situation);
+        let mut hint = None;
+        if let Some(entry) = self.tt.probe(key) {
+            self.stats.tt_hits += 1;
+            hint = entry.best_move;
+            if entry.depth >= depth {
+                if entry.depth != EXHAUSTIVE {
+                    self.hit_horizon = true;
+                }
+                match entry.bound {
+                    Bound::Exact => return entry.value,
+                    Bound::Lower => alpha = alpha.max(entry.value),
+                    Bound::Upper => beta = beta.min(entry.value),
+                }
+                if alpha >= beta {
+                    return entry.value;
+                }
+            }
+        }
+
+        // find out whether this subtree alone reaches the horizon
+        let outer_hit_horizon = self.hit_horizon;
+        self.hit_horizon = false;
+
+        let original_alpha = alpha;
+        let mut best = f32::MIN;
+        let mut best_move = None;
+        for (i, mv) in ordered_moves(game_situation, hint).enumerate() {
+            let value = self.child_value(game_situation, mv, actor, depth, alpha, beta, i == 0);
+            if self.aborted {
+                break;
+            }
+            if value > best {
+                best = value;
+                best_move = Some(mv);
+            }
+            if value > alpha {
+                alpha = value;
+            }
+            if alpha >= beta {
+                self.stats.cutoffs += 1;
+                break;
+            }
+        }
+
+        if !self.aborted {
+            let bound = if best <= original_alpha {
+                Bound::Upper
+            } else if best >= beta {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            let stored_depth = if self.hit_horizon { depth } else { EXHAUSTIVE };
+            self.tt.store(key, stored_depth, bound, best, best_move);
+        }
+        self.hit_horizon |= outer_hit_horizon;
+        best
+    }
+
+    // plays `mv`, searches the resulting position within (alpha, beta) as seen
+    // by `actor`, and takes the move back
+    #[allow(clippy::too_many_arguments)]
+    fn child_value(
+        &mut self,
+        game_situation: &mut GameSituation,
+        mv: Move,
+        actor: Player,
+        depth: i32,
+        alpha: f32,
+        beta: f32,
+        principal: bool,
+    ) -> f32 {
+        game_situation.play(mv).expect("ordered_moves only yields legal moves");
+        self.ply += 1;
+
+        let mut value = 0.0;
+        let mut full_window = principal;
+        if !principal {
+            // prove the move is no better than alpha with a null window first
+            let null_beta = if alpha == f32::MIN { alpha } else { alpha + NULL_WINDOW };
+            value = self.relative_value(game_situation, actor, depth, alpha, null_beta);
+            full_window = value > alpha && value < beta;
+        }
+        if full_window {
+            value = self.relative_value(game_situation, actor, depth, alpha, beta);
+        }
+
+        self.ply -= 1;
+        game_situation.unplay();
+        value
+    }
+
+    // the value of the current position for `actor`, who either moves again
+    // within the same turn and `depth`, or hands over to the opponent
+    fn relative_value(
+        &mut self,
+        game_situation: &mut GameSituation,
+        actor: Player,
+        depth: i32,
+        alpha: f32,
+        beta: f32,
+    ) -> f32 {
+        // every free move drops a seed in the score hole, so a turn can't
+        // go on forever
+        if game_situation.actor() == actor {
+            self.negamax(game_situation, depth, alpha, beta)
+        } else {
+            -self.negamax(game_situation, depth - 1, -beta, -alpha)
+        }
+    }
+}
+
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+impl<E: Evaluator + Clone + Send> Search<E> {
+    /// `iterative_deepening` with the root moves searched side by side on the
+    /// rayon thread pool, each by a copy of this search with a table of its
+    /// own. Every iteration searches the best move of the last one first, and
+    /// the others in parallel with null windows against the best value found
+    /// so far. `nodes` counts the nodes of all copies.
+    pub fn iterative_deepening_parallel(
+        &mut self,
+        game_situation: &GameSituation,
+        max_depth: i32,
+        time_budget: f64,
+    ) -> Option<(Move, f32)> {
+        use rayon::prelude::*;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let start = now_ms();
+        self.completed_depth = 0;
+        let actor = game_situation.actor();
+        let mut workers = ordered_moves(game_situation, None)
+            .map(|mv| {
+                let mut search = Search::with_evaluator(self.evaluator.clone());
+                search.endgame = self.endgame;
+                search.max_nodes = self.max_nodes;
+                search.deadline = start + time_budget;
+                (mv, game_situation.clone(), search)
+            })
+            .collect::<Vec<_>>();
+        let mut best = None;
+
+        for depth in 1..=max_depth {
+            let Some(((first_move, first_situation, first_search), others)) = workers.split_first_mut() else {
+                break;
+            };
+            first_search.hit_horizon = false;
+            let first_value = first_search.child_value(first_situation, *first_move, actor, depth, f32::MIN, f32::MAX, true);
+            if first_search.aborted {
+                break;
+            }
+
+            // the best value so far, as the bits of an f32
+            let alpha = AtomicU32::new(first_value.to_bits());
+            let results = others
+                .par_iter_mut()
+                .map(|(mv, game_situation, search)| {
+                    search.hit_horizon = false;
+                    let bound = f32::from_bits(alpha.load(Ordering::Acquire));
+                    let value = search.child_value(game_situation, *mv, actor, depth, bound, f32::MAX, false);
+                    if value > bound {
+                        let _ = alpha.fetch_update(Ordering::AcqRel, Ordering::Acquire, |bits| {
+                            (value > f32::from_bits(bits)).then_some(value.to_bits())
+                        });
+                    }
+                    // a value at or below the bound is only an upper bound
+                    (!search.aborted).then_some((value, value > bound, search.hit_horizon))
+                })
+                .collect::<Option<Vec<_>>>();
+            let Some(results) = results else {
+                break;
+            };
+
+            // a move failing low may tie the best value with its bound, so
+            // only exact values take the lead
+            let mut hit_horizon = first_search.hit_horizon;
+            let mut best_index = 0;
+            let mut best_value = first_value;
+            for (i, (value, exact, horizon)) in results.into_iter().enumerate() {
+                hit_horizon |= horizon;
+                if exact && value > best_value {
+                    best_index = i + 1;
+                    best_value = value;
+                }
+            }
+            best = Some((workers[best_index].0, best_value));
+            workers[..=best_index].rotate_right(1);
+            self.completed_depth = depth;
+            for (_, _, search) in &mut workers {
+                search.completed_depth = depth;
+            }
+            if !hit_horizon || now_ms() >= start + time_budget / 2.0 {
+                break;
+            }
+        }
+
+        for (_, _, search) in &workers {
+            self.stats.merge(&search.stats);
+        }
+        self.stats.elapsed_ms += now_ms() - start;
+        best
+    }
+}
+
+impl Default for Search {
+    fn default() -> Search {
+        Search::new()
+    }
+}
+
+/// The legal moves of the actor: `hint` if it is one of them, free moves,
+/// captures (biggest first), then the rest from the pit nearest to the score
+/// hole.
+pub fn ordered_moves(game_situation: &GameSituation, hint: Option<Move>) -> impl Iterator<Item = Move> {
+    let actor = game_situation.actor();
+    let mut moves = [(0, Move::new(actor, 1)); PIT_NUMBER];
+    let mut len = 0;
+    if !game_situation.ended() {
+        for pit in 1..=game_situation.rules().pit_number {
+            let mv = Move::new(actor, pit);
+            if let Some(priority) = priority(game_situation, mv) {
+                moves[len] = (if Some(mv) == hint { i32::MAX } else { priority }, mv);
+                len += 1;
+            }
+        }
+    }
+    moves[..len].sort_by_key(|&(priority, mv)| (std::cmp::Reverse(priority), std::cmp::Reverse(mv.pit)));
+    moves.into_iter().take(len).map(|(_, mv)| mv)
+}
+
+// a rough guess of how promising `mv` is, without playing it, or `None` if
+// its pit is empty
+fn priority(game_situation: &GameSituation, mv: Move) -> Option<i32> {
+    let rules = game_situation.rules();
+    let board = game_situation.board();
+    let hole_index = rules.hole_index(mv);
+    let seeds = board[hole_index];
+    if seeds == 0 {
+        return None;
+    }
+
+    let (last_hole, laps) = sowing_end(rules, hole_index, seeds);
+    let first_hole = rules.first_hole(mv.player);
+    if last_hole == rules.score_hole(mv.player) {
+        Some(1000)
+    } else if laps == 0 &&
+        (first_hole..first_hole + rules.pit_number).contains(&last_hole) &&
+        (board[last_hole] == 0 || last_hole == hole_index) {
+        let opposite = board[rules.opposite_hole(last_hole)] + (last_hole <= hole_index) as i32;
+        if opposite > 0 || rules.empty_capture {
+            Some(100 + opposite)
+        } else {
+            Some(0)
+        }
+    } else {
+        Some(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::FeatureEvaluator;
+    use mancala_core::RuleSet;
+
+    // the full-width minimax this search replaces, counting `remain_step` in
+    // sowings, or in turns if `by_turn`
+    fn minimax_by(
+        evaluator: &impl Evaluator,
+        game_situation: &mut GameSituation,
+        decide_for: Player,
+        remain_step: i32,
+        by_turn: bool,
+    ) -> f32 {
+        if game_situation.ended() || remain_step == 0 {
+            return evaluator.value_for(game_situation, decide_for);
+        }
+
+        let actor = game_situation.actor();
+        let mut values = Vec::new();
+        for pit in 1..=game_situation.rules().pit_number {
+            if game_situation.act(Move::new(actor, pit)).is_err() {
+                continue;
+            }
+            let free_move = by_turn && game_situation.actor() == actor;
+            let remain_step = if free_move { remain_step } else { remain_step - 1 };
+            values.push(minimax_by(evaluator, game_situation, decide_for, remain_step, by_turn));
+            game_situation.undo();
+        }
+
+        if actor == decide_for {
+            values.into_iter().fold(f32::MIN, f32::max)
+        } else {
+            values.into_iter().fold(f32::MAX, f32::min)
+        }
+    }
+
+    fn minimax(game_situation: &mut GameSituation, decide_for: Player, remain_step: i32) -> f32 {
+        minimax_by(&Difference, game_situation, decide_for, remain_step, true)
+    }
+
+    fn positions() -> Vec<GameSituation> {
+        vec![
+            GameSituation::new(Player::One),
+            GameSituation::from(Player::Two, &[
+                0, 5, 5, 5, 5, 4, 1,
+                4, 4, 4, 4, 4, 4, 0
+            ]),
+            GameSituation::from(Player::One, &[
+                3, 0, 1, 7, 0, 2, 12,
+                1, 0, 9, 2, 0, 4, 7
+            ]),
+            GameSituation::from(Player::Two, &[
+                0, 0, 2, 0, 1, 0, 21,
+                0, 3, 0, 1, 0, 2, 18
+            ]),
+            GameSituation::with_rules(RuleSet::kalah(4, 3), Player::One),
+        ]
+    }
+
+    #[test]
+    fn test_matches_minimax() {
+        for mut game_situation in positions() {
+            let actor = game_situation.actor();
+            for depth in 1..=6 {
+                let (mv, value) = Search::new().best_move(&mut game_situation, depth).unwrap();
+                assert_eq!(value, minimax(&mut game_situation, actor, depth), "depth {}", depth);
+
+                game_situation.act(mv).unwrap();
+                let depth = if game_situation.actor() == actor { depth } else { depth - 1 };
+                assert_eq!(minimax(&mut game_situation, actor, depth), value);
+                game_situation.undo();
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_minimax_with_features() {
+        let evaluator = FeatureEvaluator::default();
+        for mut game_situation in positions() {
+            let actor = game_situation.actor();
+            for depth in 1..=4 {
+                let (_, value) = Search::with_evaluator(evaluator).best_move(&mut game_situation, depth).unwrap();
+                assert_eq!(value, minimax_by(&evaluator, &mut game_situation, actor, depth, true));
+            }
+        }
+    }
+
+    #[test]
+    fn test_endgame_table() {
+        let table = Box::leak(Box::new(EndgameTable::generate(6)));
+        let mut game_situation = GameSituation::from(Player::Two, &[
+            1, 0, 0, 2, 0, 0, 20,
+            0, 1, 0, 0, 1, 1, 22
+        ]);
+        let mut search = Search::new();
+        let expected = search.best_move(&mut game_situation, 64).unwrap().1;
+        let nodes = search.stats.nodes;
+
+        let mut search = Search::new();
+        search.endgame = Some(table);
+        assert_eq!(search.best_move(&mut game_situation, 1).unwrap().1, expected);
+        assert!(search.stats.nodes < nodes);
+        let mut search = Search::new();
+        search.endgame = Some(table);
+        search.iterative_deepening(&mut game_situation, 64, f64::INFINITY);
+        assert_eq!(search.completed_depth, 1);
+    }
+
+    #[test]
+    fn test_free_move_combo() {
+        // 16, 15, 16 and the capture 11 win 7 seeds in one turn, which a
+        // search counting sowings only sees four plies deep
+        let mut game_situation = GameSituation::from(Player::One, &[
+            1, 0, 8, 0, 2, 1, 10,
+            5, 0, 0, 0, 3, 0, 10
+        ]);
+        assert!(minimax_by(&Difference, &mut game_situation, Player::One, 3, false) < 7.0);
+        assert_eq!(minimax_by(&Difference, &mut game_situation, Player::One, 4, false), 7.0);
+
+        let (mv, value) = Search::new().best_move(&mut game_situation, 1).unwrap();
+        assert_eq!((mv.code(), value), (16, 7.0));
+        let (mv, _) = Search::new().iterative_deepening(&mut game_situation, 64, f64::INFINITY).unwrap();
+        assert_eq!(mv.code(), 16);
+    }
+
+    #[test]
+    fn test_ordered_moves() {
+        let game_situation = GameSituation::from(Player::One, &[
+            1, 0, 2, 0, 0, 1, 0,
+            4, 4, 4, 4, 4, 4, 0
+        ]);
+        let pits = ordered_moves(&game_situation, None).map(|mv| mv.pit).collect::<Vec<_>>();
+        assert_eq!(pits, [6, 3, 1]);
+        let pits = ordered_moves(&game_situation, Some(Move::new(Player::One, 1))).map(|mv| mv.pit);
+        assert_eq!(pits.collect::<Vec<_>>(), [1, 6, 3]);
+        let pits = ordered_moves(&game_situation, Some(Move::new(Player::One, 2))).map(|mv| mv.pit);
+        assert_eq!(pits.collect::<Vec<_>>(), [6, 3, 1]);
+        assert_eq!(
+            ordered_moves(&GameSituation::new(Player::Two), None).next(),
+            Some(Move::new(Player::Two, 3))
+        );
+    }
+
+    #[test]
+    fn test_analyze() {
+        for mut game_situation in positions() {
+            let before = game_situation.clone();
+            let actor = game_situation.actor();
+            let mut search = Search::new();
+            let analyses = search.analyze(&mut game_situation, 4);
+            assert_eq!(game_situation, before);
+            assert_eq!(analyses.len(), ordered_moves(&game_situation, None).count());
+            assert_eq!(analyses[0].value, Search::new().best_move(&mut game_situation, 4).unwrap().1);
+            assert_eq!(analyses.iter().map(|analysis| analysis.nodes).sum::<u64>(), search.stats.nodes);
+            for pair in analyses.windows(2) {
+                assert!(pair[0].value >= pair[1].value);
+            }
+            for analysis in &analyses {
+                game_situation.act(analysis.mv).unwrap();
+                let depth = if game_situation.actor() == actor { 4 } else { 3 };
+                assert_eq!(analysis.value, minimax(&mut game_situation, actor, depth));
+                game_situation.undo();
+
+                // the variation is a legal line
+                assert_eq!(analysis.principal_variation[0], analysis.mv);
+                for &mv in &analysis.principal_variation {
+                    game_situation.act(mv).unwrap();
+                }
+                for _ in &analysis.principal_variation {
+                    game_situation.undo();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_transpositions_keep_values() {
+        for mut game_situation in positions() {
+            let mut search = Search::new();
+            for depth in 1..=6 {
+                // the table is kept from one depth to the next
+                let (_, value) = search.best_move(&mut game_situation, depth).unwrap();
+                assert_eq!(value, Search::new().best_move(&mut game_situation, depth).unwrap().1);
+                let actor = game_situation.actor();
+                assert_eq!(value, minimax(&mut game_situation, actor, depth));
+            }
+        }
+    }
+
+    #[test]
+    fn test_transpositions_cut_nodes() {
+        let mut game_situation = GameSituation::new(Player::One);
+        let mut search = Search::new();
+        search.best_move(&mut game_situation, 12);
+        let first_nodes = search.stats.nodes;
+        search.stats.nodes = 0;
+        search.best_move(&mut game_situation, 12);
+        assert!(search.stats.nodes * 10 < first_nodes);
+    }
+
+    #[test]
+    fn test_search_stats() {
+        let mut game_situation = GameSituation::new(Player::One);
+        let mut search = Search::new();
+        search.best_move(&mut game_situation, 6);
+        let stats = search.stats;
+        assert!(stats.leaves > 0 && stats.leaves < stats.nodes);
+        assert!(stats.cutoffs > 0 && stats.tt_hits > 0);
+        // free moves take lines deeper than the turns searched
+        assert!(stats.max_depth > 6);
+        assert!(stats.elapsed_ms >= 0.0);
+
+        search.best_move(&mut game_situation, 6);
+        assert!(search.stats.nodes > stats.nodes && search.stats.tt_hits > stats.tt_hits);
+        assert!(search.stats.elapsed_ms >= stats.elapsed_ms);
+    }
+
+    #[test]
+    fn test_iterative_deepening() {
+        for mut game_situation in positions() {
+            let expected = Search::new().best_move(&mut game_situation, 7).unwrap().1;
+            let mut search = Search::new();
+            let (_, value) = search.iterative_deepening(&mut game_situation, 7, f64::INFINITY).unwrap();
+            assert_eq!(value, expected);
+            assert_eq!(search.completed_depth, 7);
+        }
+    }
+
+    #[test]
+    fn test_iterative_deepening_out_of_time() {
+        let mut game_situation = GameSituation::new(Player::One);
+        let mut search = Search::new();
+        assert!(search.iterative_deepening(&mut game_situation, 64, 0.0).is_some());
+        assert!(search.completed_depth >= 1 && search.completed_depth < 64);
+        assert_eq!(game_situation, GameSituation::new(Player::One));
+    }
+
+    #[test]
+    fn test_iterative_deepening_reused() {
+        let mut search = Search::new();
+        search.iterative_deepening(&mut GameSituation::new(Player::One), 7, f64::INFINITY);
+        // look at the clock on the first node of the next search
+        search.stats.nodes = CLOCK_INTERVAL - 1;
+        let mut game_situation = positions().pop().unwrap();
+        assert!(search.iterative_deepening(&mut game_situation, 64, 0.0).is_some());
+        assert_eq!(search.completed_depth, 1);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_iterative_deepening_parallel() {
+        for mut game_situation in positions() {
+            let expected = Search::new().best_move(&mut game_situation, 6).unwrap().1;
+            let mut search = Search::new();
+            let (_, value) = search.iterative_deepening_parallel(&game_situation, 6, f64::INFINITY).unwrap();
+            assert_eq!(value, expected);
+            assert_eq!(search.completed_depth, 6);
+            assert!(search.stats.nodes > 0);
+        }
+
+        // one move is best, so moves failing low at its value must not take
+        // its place
+        let statuses = [
+            [4, 0, 1, 6, 6, 6, 1, 4, 4, 4, 4, 4, 4, 0],
+            [1, 0, 0, 1, 1, 0, 19, 1, 1, 1, 1, 10, 1, 11],
+        ];
+        for status in statuses {
+            let mut game_situation = GameSituation::from(Player::One, &status);
+            let analyses = Search::new().analyze(&mut game_situation, 6);
+            assert!(analyses[0].value > analyses[1].value);
+            for _ in 0..20 {
+                let mut search = Search::new();
+                let (mv, value) = search.iterative_deepening_parallel(&game_situation, 6, f64::INFINITY).unwrap();
+                assert_eq!((mv, value), (analyses[0].mv, analyses[0].value));
+            }
+        }
+        let mut search = Search::new();
+        assert!(search.iterative_deepening_parallel(&GameSituation::new(Player::One), 64, 0.0).is_some());
+        assert!(search.completed_depth >= 1 && search.completed_depth < 64);
+    }
+
+    #[test]
+    fn test_iterative_deepening_stops_at_end_of_game() {
+        let mut game_situation = GameSituation::from(Player::One, &[
+            0, 0, 0, 0, 1, 1, 20,
+            0, 0, 0, 0, 1, 0, 25
+        ]);
+        let mut search = Search::new();
+        search.iterative_deepening(&mut game_situation, 64, f64::INFINITY);
+        assert!(search.completed_depth < 10);
+    }
+}
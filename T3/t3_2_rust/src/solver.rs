@@ -0,0 +1,81 @@
+//! Exact values of positions under perfect play, for small boards and late
+//! midgames of the standard one.
+
+use mancala_core::{GameSituation, Move};
+
+use crate::search::Search;
+
+// turns never run out before the seeds do
+const UNLIMITED_DEPTH: i32 = i32::MAX - 1;
+
+/// The outcome of a position when both players play perfectly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Solution {
+    /// The final score of the player to move minus the opponent's.
+    pub value: i32,
+    /// A move that achieves `value`, `None` if the game has ended.
+    pub best_move: Option<Move>,
+}
+
+/// Solves `game_situation` by MTD(f) searches to the end of the game, or
+/// gives up with `None` once `search` has visited `max_nodes` more nodes.
+/// `search` may hold a larger table or an endgame table for harder positions.
+pub fn solve(search: &mut Search, game_situation: &mut GameSituation, max_nodes: u64) -> Option<Solution> {
+    let actor = game_situation.actor();
+    let lead = game_situation.score(actor) - game_situation.score(actor.opponent());
+    if game_situation.ended() {
+        return Some(Solution { value: lead, best_move: None });
+    }
+
+    search.max_nodes = search.stats.nodes.saturating_add(max_nodes);
+    let result = search.mtdf(game_situation, UNLIMITED_DEPTH, lead as f32);
+    search.max_nodes = u64::MAX;
+    let (mv, value) = result?;
+    Some(Solution { value: value as i32, best_move: Some(mv) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mancala_core::{Player, RuleSet};
+
+    #[test]
+    fn test_solve() {
+        let mut game_situation = GameSituation::with_rules(RuleSet::kalah(3, 3), Player::One);
+        let (_, expected) = Search::new().best_move(&mut game_situation, UNLIMITED_DEPTH).unwrap();
+        let solution = solve(&mut Search::new(), &mut game_situation, u64::MAX).unwrap();
+        assert_eq!(solution.value, expected as i32);
+
+        // the move keeps the value
+        game_situation.act(solution.best_move.unwrap()).unwrap();
+        let next = solve(&mut Search::new(), &mut game_situation, u64::MAX).unwrap();
+        let value = if game_situation.actor() == Player::One { next.value } else { -next.value };
+        assert_eq!(value, solution.value);
+
+        let mut game_situation = GameSituation::from(Player::Two, &[
+            1, 0, 3, 0, 2, 0, 20,
+            0, 2, 0, 1, 0, 4, 15
+        ]);
+        let (_, expected) = Search::new().best_move(&mut game_situation, UNLIMITED_DEPTH).unwrap();
+        assert_eq!(solve(&mut Search::new(), &mut game_situation, u64::MAX).unwrap().value, expected as i32);
+    }
+
+    #[test]
+    fn test_solve_out_of_nodes() {
+        let mut game_situation = GameSituation::new(Player::One);
+        let mut search = Search::new();
+        assert_eq!(solve(&mut search, &mut game_situation, 1000), None);
+        assert!(search.stats.nodes < 1100);
+        assert_eq!(game_situation, GameSituation::new(Player::One));
+
+        let mut ended = GameSituation::from(Player::One, &[
+            0, 0, 0, 0, 0, 1, 20,
+            0, 0, 0, 0, 0, 1, 26
+        ]);
+        ended.act(Move::new(Player::One, 6)).unwrap();
+        let actor = ended.actor();
+        let solution = solve(&mut search, &mut ended, 1000).unwrap();
+        let value = ended.score(actor) - ended.score(actor.opponent());
+        assert_eq!(solution, Solution { value, best_move: None });
+    }
+}
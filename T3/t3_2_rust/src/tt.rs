@@ -0,0 +1,157 @@
+use mancala_core::{GameSituation, Move, Player, HOLE_NUMBER};
+
+// seed counts beyond this share keys, which only costs a few more collisions
+const ZOBRIST_SEEDS: usize = 128;
+
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (state, z ^ (z >> 31))
+}
+
+const fn zobrist_keys() -> ([[u64; ZOBRIST_SEEDS]; HOLE_NUMBER], u64) {
+    let mut keys = [[0; ZOBRIST_SEEDS]; HOLE_NUMBER];
+    let mut state = 0x4D41_4E43_414C_4121;
+    let mut hole = 0;
+    while hole < HOLE_NUMBER {
+        let mut seeds = 0;
+        while seeds < ZOBRIST_SEEDS {
+            let (next_state, key) = splitmix64(state);
+            state = next_state;
+            keys[hole][seeds] = key;
+            seeds += 1;
+        }
+        hole += 1;
+    }
+    let (_, player_two) = splitmix64(state);
+    (keys, player_two)
+}
+
+const ZOBRIST: ([[u64; ZOBRIST_SEEDS]; HOLE_NUMBER], u64) = zobrist_keys();
+
+/// Zobrist hash of the board and the player to move.
+pub fn hash(game_situation: &GameSituation) -> u64 {
+    let (keys, player_two) = &ZOBRIST;
+    let mut key = if game_situation.actor() == Player::Two { *player_two } else { 0 };
+    for (hole, &seeds) in game_situation.board().iter().enumerate() {
+        key ^= keys[hole][seeds as usize % ZOBRIST_SEEDS];
+    }
+    key
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    /// The value is at least the stored one (the search failed high).
+    Lower,
+    /// The value is at most the stored one (the search failed low).
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Entry {
+    key: u64,
+    pub depth: i32,
+    pub bound: Bound,
+    pub value: f32,
+    pub best_move: Option<Move>,
+    generation: u8,
+}
+
+/// A fixed-size hash table of searched positions. A slot is taken over by a
+/// new position when it is empty, left from an earlier search, or searched
+/// no deeper than the new one.
+pub struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+    generation: u8,
+}
+
+impl TranspositionTable {
+    /// A table of `2^bits` entries.
+    pub fn new(bits: u32) -> TranspositionTable {
+        TranspositionTable {
+            entries: vec![None; 1 << bits],
+            generation: 0,
+        }
+    }
+
+    /// Marks the entries stored so far as belonging to an earlier search,
+    /// which hides them from `probe`.
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+        // once in 256 searches, before an old generation comes back
+        if self.generation == 0 {
+            self.entries.fill(None);
+        }
+    }
+
+    pub fn probe(&self, key: u64) -> Option<&Entry> {
+        self.entries[self.index(key)]
+            .as_ref()
+            .filter(|entry| entry.key == key && entry.generation == self.generation)
+    }
+
+    pub fn store(&mut self, key: u64, depth: i32, bound: Bound, value: f32, best_move: Option<Move>) {
+        let index = self.index(key);
+        let generation = self.generation;
+        let replace = match &self.entries[index] {
+            None => true,
+            Some(entry) => entry.key == key || entry.generation != generation || depth >= entry.depth,
+        };
+        if replace {
+            self.entries[index] = Some(Entry { key, depth, bound, value, best_move, generation });
+        }
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key as usize) & (self.entries.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_tells_positions_apart() {
+        let mut game_situation = GameSituation::new(Player::One);
+        let start = hash(&game_situation);
+        assert_ne!(start, hash(&GameSituation::new(Player::Two)));
+
+        game_situation.act(Move::new(Player::One, 1)).unwrap();
+        assert_ne!(hash(&game_situation), start);
+        game_situation.undo();
+        assert_eq!(hash(&game_situation), start);
+    }
+
+    #[test]
+    fn test_replacement() {
+        let mut table = TranspositionTable::new(4);
+        table.store(1, 5, Bound::Exact, 2.0, None);
+        table.store(17, 3, Bound::Lower, 1.0, None);
+        assert_eq!(table.probe(1).map(|entry| entry.depth), Some(5));
+        assert!(table.probe(17).is_none());
+
+        table.new_search();
+        table.store(17, 3, Bound::Lower, 1.0, None);
+        assert!(table.probe(1).is_none());
+        assert_eq!(table.probe(17).map(|entry| entry.bound), Some(Bound::Lower));
+    }
+
+    #[test]
+    fn test_new_search_forgets() {
+        let mut table = TranspositionTable::new(4);
+        table.store(1, 5, Bound::Exact, 2.0, None);
+        table.new_search();
+        assert!(table.probe(1).is_none());
+        // nor does the entry come back with its generation
+        for _ in 0..u8::MAX {
+            table.new_search();
+        }
+        assert!(table.probe(1).is_none());
+        table.store(1, 5, Bound::Exact, 2.0, None);
+        assert!(table.probe(1).is_some());
+    }
+}
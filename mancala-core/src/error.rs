@@ -0,0 +1,49 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use crate::Player;
+
+/// Why a move was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IllegalMove {
+    /// The game has already ended.
+    GameOver,
+    /// It is `expected`'s turn to move.
+    NotYourTurn { expected: Player },
+    /// The chosen pit (1 - 6) holds no seeds.
+    EmptyPit { pit: usize },
+    /// The code is not in 11 - 16 or 21 - 26.
+    MalformedCode { code: i32 },
+    /// In Oware, the opponent has no seeds left and the move gives them none.
+    MustFeed,
+}
+
+impl Display for IllegalMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            IllegalMove::GameOver => write!(f, "the game has already ended"),
+            IllegalMove::NotYourTurn { expected } => {
+                write!(f, "it is player {}'s turn", expected.number())
+            }
+            IllegalMove::EmptyPit { pit } => write!(f, "pit {} is empty", pit),
+            IllegalMove::MalformedCode { code } => write!(f, "{} is not a move code", code),
+            IllegalMove::MustFeed => write!(f, "the opponent must be given seeds"),
+        }
+    }
+}
+
+impl Error for IllegalMove {}
+
+/// A rule set description `RuleSet::from_str` does not understand.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseRuleSetError {
+    pub spec: String,
+}
+
+impl Display for ParseRuleSetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown rule set `{}`", self.spec)
+    }
+}
+
+impl Error for ParseRuleSetError {}
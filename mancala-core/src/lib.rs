@@ -0,0 +1,29 @@
+//! Rules engines for Kalah (`GameSituation`) and Oware (`OwareSituation`)
+//! shared by the wasm crates under `/T2` and `/T3`.
+//!
+//! The board is laid out the same way as the `status` array of the T3 tasks:
+//! holes 11 - 16 at indices 0 - 5, player 1's score hole at 6, holes 21 - 26
+//! at indices 7 - 12 and player 2's score hole at 13. Variants with fewer pits
+//! per side (see `RuleSet`) use the first holes of the same array.
+
+mod error;
+mod moves;
+mod oware;
+mod player;
+mod rules;
+mod situation;
+
+pub use error::{IllegalMove, ParseRuleSetError};
+pub use moves::{Move, MoveOutcome};
+pub use oware::{OwareSituation, TOTAL_SEEDS};
+pub use player::Player;
+pub use rules::{RuleSet, Sweep};
+pub use situation::GameSituation;
+
+/// Holes of the standard board, and the most any `RuleSet` uses.
+pub const HOLE_NUMBER: usize = 14;
+/// Pits per side of the standard board, and the most any `RuleSet` allows.
+pub const PIT_NUMBER: usize = 6;
+
+pub const PLAYER_1_SCORE_HOLE: usize = 6;
+pub const PLAYER_2_SCORE_HOLE: usize = 13;
@@ -0,0 +1,74 @@
+use crate::{IllegalMove, Player, PIT_NUMBER};
+
+/// Sowing the seeds of `pit` (1 - 6, counted from the opponent's score hole
+/// towards the player's own) on behalf of `player`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Move {
+    pub player: Player,
+    pub pit: usize,
+}
+
+impl Move {
+    pub fn new(player: Player, pit: usize) -> Move {
+        assert!((1..=PIT_NUMBER).contains(&pit));
+        Move { player, pit }
+    }
+
+    /// The two digit move code, e.g. `23` for pit 3 of player two.
+    pub fn code(self) -> i32 {
+        self.player.number() * 10 + self.pit as i32
+    }
+}
+
+impl TryFrom<i32> for Move {
+    type Error = IllegalMove;
+
+    fn try_from(code: i32) -> Result<Move, IllegalMove> {
+        let player = match code / 10 {
+            1 => Player::One,
+            2 => Player::Two,
+            _ => return Err(IllegalMove::MalformedCode { code }),
+        };
+        let pit = code % 10;
+        if pit < 1 || pit > PIT_NUMBER as i32 {
+            return Err(IllegalMove::MalformedCode { code });
+        }
+        Ok(Move { player, pit: pit as usize })
+    }
+}
+
+impl From<Move> for i32 {
+    fn from(mv: Move) -> i32 {
+        mv.code()
+    }
+}
+
+/// What happened after a legal move.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MoveOutcome {
+    /// The last seed fell in the mover's score hole, so the mover plays again.
+    pub extra_turn: bool,
+    /// The move captured seeds: in Kalah the last seed fell in an empty pit
+    /// of the mover, in Oware it made 2s or 3s on the opponent's side.
+    pub capture: bool,
+    pub ended: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_codes() {
+        assert_eq!(Move::try_from(11), Ok(Move::new(Player::One, 1)));
+        assert_eq!(Move::try_from(26), Ok(Move::new(Player::Two, 6)));
+        assert_eq!(Move::new(Player::Two, 3).code(), 23);
+    }
+
+    #[test]
+    fn test_malformed_codes() {
+        for code in [-11, 0, 10, 17, 20, 27, 31, 116] {
+            assert_eq!(Move::try_from(code), Err(IllegalMove::MalformedCode { code }));
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{IllegalMove, Move, MoveOutcome, Player, HOLE_NUMBER, PIT_NUMBER, PLAYER_1_SCORE_HOLE, PLAYER_2_SCORE_HOLE};
+
+/// A game of Oware (Abapa rules).
+///
+/// It uses the same 14-hole board as `GameSituation`, except that seeds are
+/// never sown into the score holes: they only keep the seeds each player has
+/// captured.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwareSituation {
+    actor: Player,
+    board: [i32; HOLE_NUMBER],
+    ended: bool,
+}
+
+const INITIAL_SEEDS: i32 = 4;
+/// Seeds in a game of Oware, captured or not.
+pub const TOTAL_SEEDS: i32 = 2 * PIT_NUMBER as i32 * INITIAL_SEEDS;
+
+impl OwareSituation {
+    /// The initial board, with `first_actor` to move.
+    pub fn new(first_actor: Player) -> OwareSituation {
+        let mut situation = OwareSituation::from(first_actor, &[INITIAL_SEEDS; HOLE_NUMBER]);
+
+        situation.board[PLAYER_1_SCORE_HOLE] = 0;
+        situation.board[PLAYER_2_SCORE_HOLE] = 0;
+
+        situation
+    }
+
+    /// An arbitrary position given as a 14-element `status` array, the score
+    /// holes holding the seeds captured so far.
+    pub fn from(first_actor: Player, status: &[i32]) -> OwareSituation {
+        let mut situation = OwareSituation {
+            actor: first_actor,
+            board: [0; HOLE_NUMBER],
+            ended: false,
+        };
+
+        situation.board.copy_from_slice(&status[..HOLE_NUMBER]);
+
+        situation
+    }
+
+    /// The player who should move next.
+    pub fn actor(&self) -> Player {
+        self.actor
+    }
+
+    pub fn board(&self) -> &[i32; HOLE_NUMBER] {
+        &self.board
+    }
+
+    pub fn ended(&self) -> bool {
+        self.ended
+    }
+
+    /// Seeds captured by `player`.
+    pub fn score(&self, player: Player) -> i32 {
+        self.board[Self::score_hole(player)]
+    }
+
+    /// Plays `mv`, leaving the situation untouched if the move is illegal.
+    pub fn act(&mut self, mv: Move) -> Result<MoveOutcome, IllegalMove> {
+        // the game should not be ended
+        if self.ended {
+            return Err(IllegalMove::GameOver);
+        }
+
+        // the actor should be correct
+        if mv.player != self.actor {
+            return Err(IllegalMove::NotYourTurn { expected: self.actor });
+        }
+
+        // the hole should not be empty
+        let hole_index = Self::hole_index(mv);
+        if self.board[hole_index] == 0 {
+            return Err(IllegalMove::EmptyPit { pit: mv.pit });
+        }
+
+        // a starving opponent must be fed
+        if self.side_seeds(self.actor.opponent()) == 0 && !self.feeds(hole_index) {
+            return Err(IllegalMove::MustFeed);
+        }
+
+        let mut outcome = MoveOutcome::default();
+
+        // sow, skipping the emptied hole on laps of 12 seeds or more
+        let mut mancala_pieces = self.board[hole_index];
+        self.board[hole_index] = 0;
+        let mut current_hole = hole_index;
+        while mancala_pieces > 0 {
+            current_hole = Self::next_hole(current_hole);
+            if current_hole != hole_index {
+                self.board[current_hole] += 1;
+                mancala_pieces -= 1;
+            }
+        }
+
+        outcome.capture = self.try_capture(current_hole);
+
+        // change actor
+        self.actor = self.actor.opponent();
+
+        outcome.ended = self.try_end();
+        Ok(outcome)
+    }
+
+    fn hole_index(mv: Move) -> usize {
+        Self::first_hole(mv.player) + mv.pit - 1
+    }
+
+    fn first_hole(player: Player) -> usize {
+        match player {
+            Player::One => 0,
+            Player::Two => PLAYER_1_SCORE_HOLE + 1,
+        }
+    }
+
+    fn score_hole(player: Player) -> usize {
+        match player {
+            Player::One => PLAYER_1_SCORE_HOLE,
+            Player::Two => PLAYER_2_SCORE_HOLE,
+        }
+    }
+
+    fn owner(hole_index: usize) -> Player {
+        if hole_index < PLAYER_1_SCORE_HOLE {
+            Player::One
+        } else {
+            Player::Two
+        }
+    }
+
+    fn next_hole(hole_index: usize) -> usize {
+        match hole_index + 1 {
+            PLAYER_1_SCORE_HOLE => PLAYER_1_SCORE_HOLE + 1,
+            PLAYER_2_SCORE_HOLE => 0,
+            next => next,
+        }
+    }
+
+    fn side_seeds(&self, player: Player) -> i32 {
+        let first_hole = Self::first_hole(player);
+        self.board[first_hole..first_hole + PIT_NUMBER].iter().sum()
+    }
+
+    // whether sowing `hole_index` puts at least one seed on the other side
+    fn feeds(&self, hole_index: usize) -> bool {
+        let distance = Self::score_hole(Self::owner(hole_index)) - hole_index;
+        self.board[hole_index] >= distance as i32
+    }
+
+    // captures 2s and 3s backwards from the last sown hole, unless that would
+    // take every seed of the opponent (grand slam)
+    fn try_capture(&mut self, last_hole: usize) -> bool {
+        let opponent = self.actor.opponent();
+        let first_hole = Self::first_hole(opponent);
+        if Self::owner(last_hole) != opponent {
+            return false;
+        }
+
+        let mut captured = 0;
+        let mut current_hole = last_hole;
+        while (2..=3).contains(&self.board[current_hole]) {
+            captured += self.board[current_hole];
+            if current_hole == first_hole {
+                break;
+            }
+            current_hole -= 1;
+        }
+        if captured == 0 || captured == self.side_seeds(opponent) {
+            return false;
+        }
+
+        let mut current_hole = last_hole;
+        while current_hole >= first_hole && (2..=3).contains(&self.board[current_hole]) {
+            self.board[Self::score_hole(self.actor)] += self.board[current_hole];
+            self.board[current_hole] = 0;
+            if current_hole == first_hole {
+                break;
+            }
+            current_hole -= 1;
+        }
+        true
+    }
+
+    fn try_end(&mut self) -> bool {
+        // a majority of the seeds wins, and 24 all is a draw
+        if self.score(Player::One) * 2 > TOTAL_SEEDS ||
+            self.score(Player::Two) * 2 > TOTAL_SEEDS ||
+            self.score(Player::One) * 2 == TOTAL_SEEDS && self.score(Player::Two) * 2 == TOTAL_SEEDS {
+            self.ended = true;
+        }
+
+        // if the opponent of the actor is starving and can't be fed, or the
+        // actor has nothing to sow, the seeds left go to their owners
+        let first_hole = Self::first_hole(self.actor);
+        let can_feed = (first_hole..first_hole + PIT_NUMBER)
+            .any(|i| self.board[i] > 0 && self.feeds(i));
+        if self.side_seeds(self.actor) == 0 ||
+            self.side_seeds(self.actor.opponent()) == 0 && !can_feed {
+            for player in [Player::One, Player::Two] {
+                let first_hole = Self::first_hole(player);
+                for i in first_hole..first_hole + PIT_NUMBER {
+                    self.board[Self::score_hole(player)] += self.board[i];
+                    self.board[i] = 0;
+                }
+            }
+            self.ended = true;
+        }
+        self.ended
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(oware_situation: &mut OwareSituation, code: i32) -> Result<MoveOutcome, IllegalMove> {
+        oware_situation.act(Move::try_from(code).unwrap())
+    }
+
+    #[test]
+    fn test_sow_skips_score_holes() {
+        let mut oware_situation = OwareSituation::new(Player::One);
+        assert_eq!(play(&mut oware_situation, 16), Ok(MoveOutcome::default()));
+        assert_eq!(oware_situation.board(), &[4, 4, 4, 4, 4, 0, 0, 5, 5, 5, 5, 4, 4, 0]);
+        assert_eq!(oware_situation.actor(), Player::Two);
+    }
+
+    #[test]
+    fn test_skip_origin_on_laps() {
+        let mut oware_situation = OwareSituation::from(Player::One, &[
+            12, 0, 0, 0, 0, 0, 0,
+            1, 1, 1, 1, 1, 1, 0
+        ]);
+        play(&mut oware_situation, 11).unwrap();
+        assert_eq!(oware_situation.board(), &[0, 2, 1, 1, 1, 1, 0, 2, 2, 2, 2, 2, 2, 0]);
+    }
+
+    #[test]
+    fn test_chained_capture() {
+        let mut oware_situation = OwareSituation::from(Player::One, &[
+            0, 0, 0, 0, 0, 3, 0,
+            1, 2, 1, 4, 4, 4, 0
+        ]);
+        assert_eq!(play(&mut oware_situation, 16).map(|outcome| outcome.capture), Ok(true));
+        assert_eq!(oware_situation.score(Player::One), 7);
+        assert_eq!(oware_situation.board(), &[0, 0, 0, 0, 0, 0, 7, 0, 0, 0, 4, 4, 4, 0]);
+    }
+
+    #[test]
+    fn test_grand_slam_captures_nothing() {
+        let mut oware_situation = OwareSituation::from(Player::One, &[
+            0, 0, 0, 0, 1, 2, 0,
+            1, 1, 0, 0, 0, 0, 0
+        ]);
+        assert_eq!(play(&mut oware_situation, 16).map(|outcome| outcome.capture), Ok(false));
+        assert_eq!(oware_situation.board(), &[0, 0, 0, 0, 1, 0, 0, 2, 2, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_must_feed() {
+        let mut oware_situation = OwareSituation::from(Player::One, &[
+            1, 0, 0, 0, 0, 1, 10,
+            0, 0, 0, 0, 0, 0, 10
+        ]);
+        assert_eq!(play(&mut oware_situation, 11), Err(IllegalMove::MustFeed));
+        assert_eq!(play(&mut oware_situation, 16).map(|outcome| outcome.ended), Ok(false));
+    }
+
+    #[test]
+    fn test_end_when_opponent_can_not_be_fed() {
+        let mut oware_situation = OwareSituation::from(Player::Two, &[
+            0, 0, 0, 0, 0, 1, 10,
+            1, 3, 0, 0, 0, 0, 10
+        ]);
+        assert_eq!(play(&mut oware_situation, 21).map(|outcome| outcome.ended), Ok(false));
+        assert_eq!(play(&mut oware_situation, 16).map(|outcome| outcome.ended), Ok(true));
+        assert_eq!(oware_situation.score(Player::Two), 15);
+        assert_eq!(play(&mut oware_situation, 22), Err(IllegalMove::GameOver));
+    }
+
+    #[test]
+    fn test_end_on_majority() {
+        let mut oware_situation = OwareSituation::from(Player::One, &[
+            0, 0, 0, 0, 0, 1, 23,
+            1, 1, 1, 1, 1, 1, 0
+        ]);
+        assert_eq!(play(&mut oware_situation, 16).map(|outcome| outcome.ended), Ok(true));
+        assert_eq!(oware_situation.score(Player::One), 25);
+    }
+}
@@ -0,0 +1,32 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Player {
+    One,
+    Two,
+}
+
+impl Player {
+    /// Maps the `flag` argument of the wasm exports: `1` is player one and
+    /// anything else player two.
+    pub fn from_flag(flag: i32) -> Player {
+        if flag == 1 {
+            Player::One
+        } else {
+            Player::Two
+        }
+    }
+
+    /// The player number used in move codes and `flag` arguments.
+    pub fn number(self) -> i32 {
+        match self {
+            Player::One => 1,
+            Player::Two => 2,
+        }
+    }
+
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+}
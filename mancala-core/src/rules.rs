@@ -0,0 +1,172 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::{Move, ParseRuleSetError, Player, HOLE_NUMBER, PIT_NUMBER};
+
+/// Where the seeds left on the board go once one side runs empty.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sweep {
+    /// Each player keeps the seeds on their own side (standard Kalah).
+    ToOwner,
+    /// The player whose side ran empty takes the seeds left on the other one.
+    ToOpponent,
+}
+
+/// A Kalah variant: `pit_number` pits per side holding `initial_seeds` seeds
+/// each at the start.
+///
+/// The holes are laid out like the standard board: player one's pits, player
+/// one's score hole, player two's pits, player two's score hole.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RuleSet {
+    pub pit_number: usize,
+    pub initial_seeds: i32,
+    /// The last seed falling in an empty pit of the mover is captured even if
+    /// the pit opposite to it is empty.
+    pub empty_capture: bool,
+    pub sweep: Sweep,
+}
+
+impl RuleSet {
+    /// Kalah(`pit_number`, `initial_seeds`) with the standard capture and
+    /// sweep rules.
+    pub fn kalah(pit_number: usize, initial_seeds: i32) -> RuleSet {
+        assert!((1..=PIT_NUMBER).contains(&pit_number));
+        RuleSet {
+            pit_number,
+            initial_seeds,
+            empty_capture: false,
+            sweep: Sweep::ToOwner,
+        }
+    }
+
+    /// Number of holes in use, score holes included.
+    pub fn hole_number(&self) -> usize {
+        2 * self.pit_number + 2
+    }
+
+    pub fn total_seeds(&self) -> i32 {
+        2 * self.pit_number as i32 * self.initial_seeds
+    }
+
+    /// Board index of `player`'s pit 1.
+    pub fn first_hole(&self, player: Player) -> usize {
+        match player {
+            Player::One => 0,
+            Player::Two => self.pit_number + 1,
+        }
+    }
+
+    pub fn score_hole(&self, player: Player) -> usize {
+        self.first_hole(player) + self.pit_number
+    }
+
+    /// Board index of the pit sown by `mv`.
+    pub fn hole_index(&self, mv: Move) -> usize {
+        self.first_hole(mv.player) + mv.pit - 1
+    }
+
+    pub fn opposite_hole(&self, hole_index: usize) -> usize {
+        assert!(
+            hole_index != self.score_hole(Player::One) &&
+                hole_index != self.score_hole(Player::Two)
+        );
+        2 * self.pit_number - hole_index
+    }
+
+    /// The starting board, padded with zeros up to `HOLE_NUMBER`.
+    pub fn initial_board(&self) -> [i32; HOLE_NUMBER] {
+        let mut board = [0; HOLE_NUMBER];
+        for player in [Player::One, Player::Two] {
+            let first_hole = self.first_hole(player);
+            board[first_hole..first_hole + self.pit_number].fill(self.initial_seeds);
+        }
+        board
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> RuleSet {
+        RuleSet::kalah(PIT_NUMBER, 4)
+    }
+}
+
+/// Formats as `kalah(6,4)`, followed by `+empty-capture` and
+/// `+sweep-to-opponent` for the non-standard rules.
+impl Display for RuleSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "kalah({},{})", self.pit_number, self.initial_seeds)?;
+        if self.empty_capture {
+            write!(f, "+empty-capture")?;
+        }
+        if self.sweep == Sweep::ToOpponent {
+            write!(f, "+sweep-to-opponent")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RuleSet {
+    type Err = ParseRuleSetError;
+
+    fn from_str(spec: &str) -> Result<RuleSet, ParseRuleSetError> {
+        let error = || ParseRuleSetError { spec: spec.to_string() };
+
+        let mut parts = spec.trim().split('+');
+        let kalah = parts.next().unwrap_or_default().trim();
+        let mut rules = if kalah.is_empty() {
+            RuleSet::default()
+        } else {
+            let arguments = kalah.strip_prefix("kalah(")
+                .and_then(|rest| rest.strip_suffix(')'))
+                .ok_or_else(error)?;
+            let (pit_number, initial_seeds) = arguments.split_once(',').ok_or_else(error)?;
+            let pit_number = pit_number.trim().parse::<usize>().map_err(|_| error())?;
+            let initial_seeds = initial_seeds.trim().parse::<i32>().map_err(|_| error())?;
+            if !(1..=PIT_NUMBER).contains(&pit_number) || initial_seeds < 1 {
+                return Err(error());
+            }
+            RuleSet::kalah(pit_number, initial_seeds)
+        };
+
+        for option in parts {
+            match option.trim() {
+                "empty-capture" => rules.empty_capture = true,
+                "sweep-to-opponent" => rules.sweep = Sweep::ToOpponent,
+                _ => return Err(error()),
+            }
+        }
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let rules = RuleSet::kalah(4, 3);
+        assert_eq!(rules.hole_number(), 10);
+        assert_eq!(rules.score_hole(Player::One), 4);
+        assert_eq!(rules.score_hole(Player::Two), 9);
+        assert_eq!(rules.hole_index(Move::new(Player::Two, 1)), 5);
+        assert_eq!(rules.opposite_hole(0), 8);
+        assert_eq!(rules.initial_board(), [3, 3, 3, 3, 0, 3, 3, 3, 3, 0, 0, 0, 0, 0]);
+        assert_eq!(RuleSet::default().hole_index(Move::new(Player::Two, 1)), 7);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("kalah(6,4)".parse(), Ok(RuleSet::default()));
+        assert_eq!("".parse(), Ok(RuleSet::default()));
+        assert_eq!("kalah(6, 3)".parse(), Ok(RuleSet::kalah(6, 3)));
+        let rules: RuleSet = "kalah(6,6)+empty-capture+sweep-to-opponent".parse().unwrap();
+        assert!(rules.empty_capture);
+        assert_eq!(rules.sweep, Sweep::ToOpponent);
+        assert_eq!(rules.to_string(), "kalah(6,6)+empty-capture+sweep-to-opponent");
+        for spec in ["kalah(7,4)", "kalah(6,0)", "kalah(6)", "oware", "kalah(6,4)+no-such-rule"] {
+            assert!(spec.parse::<RuleSet>().is_err(), "{}", spec);
+        }
+    }
+}
@@ -0,0 +1,479 @@
+use crate::{IllegalMove, Move, MoveOutcome, Player, RuleSet, Sweep, HOLE_NUMBER};
+
+#[derive(Clone, Debug)]
+pub struct GameSituation {
+    rules: RuleSet,
+    actor: Player,
+    board: [i32; HOLE_NUMBER],
+    ended: bool,
+    journal: Vec<JournalEntry>,
+    undone: Vec<Move>,
+}
+
+/// Everything needed to take back one move. The sown pits are not stored:
+/// they follow from the origin pit and the number of seeds lifted from it.
+#[derive(Clone, Debug)]
+struct JournalEntry {
+    mv: Move,
+    seeds: i32,
+    // the hole the last seed fell in, and the seeds taken from its opposite
+    capture: Option<(usize, i32)>,
+    extra_turn: bool,
+    // the board right before the end-of-game sweep
+    sweep: Option<[i32; HOLE_NUMBER]>,
+}
+
+impl GameSituation {
+    /// The initial Kalah(6,4) board, with `first_actor` to move.
+    pub fn new(first_actor: Player) -> GameSituation {
+        GameSituation::with_rules(RuleSet::default(), first_actor)
+    }
+
+    /// An arbitrary Kalah(6,4) position given as a 14-element `status` array.
+    pub fn from(first_actor: Player, status: &[i32]) -> GameSituation {
+        GameSituation::from_with_rules(RuleSet::default(), first_actor, status)
+    }
+
+    /// The initial board of `rules`, with `first_actor` to move.
+    pub fn with_rules(rules: RuleSet, first_actor: Player) -> GameSituation {
+        GameSituation::from_with_rules(rules, first_actor, &rules.initial_board())
+    }
+
+    /// An arbitrary position under `rules`, given as a `status` array of
+    /// `rules.hole_number()` elements.
+    pub fn from_with_rules(rules: RuleSet, first_actor: Player, status: &[i32]) -> GameSituation {
+        let mut situation = GameSituation {
+            rules,
+            actor: first_actor,
+            board: [0; HOLE_NUMBER],
+            ended: false,
+            journal: Vec::new(),
+            undone: Vec::new(),
+        };
+
+        let hole_number = rules.hole_number();
+        situation.board[..hole_number].copy_from_slice(&status[..hole_number]);
+
+        situation
+    }
+
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    /// The player who should move next.
+    pub fn actor(&self) -> Player {
+        self.actor
+    }
+
+    /// The `rules.hole_number()` holes in use.
+    pub fn board(&self) -> &[i32] {
+        &self.board[..self.rules.hole_number()]
+    }
+
+    pub fn ended(&self) -> bool {
+        self.ended
+    }
+
+    /// Seeds in the score hole of `player`.
+    pub fn score(&self, player: Player) -> i32 {
+        self.board[self.rules.score_hole(player)]
+    }
+
+    /// The moves played so far and not undone, oldest first.
+    pub fn history(&self) -> impl Iterator<Item = Move> + '_ {
+        self.journal.iter().map(|entry| entry.mv)
+    }
+
+    /// Plays `mv`, leaving the situation untouched if the move is illegal.
+    ///
+    /// A legal move is recorded in the journal and discards any moves that
+    /// could have been redone.
+    pub fn act(&mut self, mv: Move) -> Result<MoveOutcome, IllegalMove> {
+        let outcome = self.play(mv)?;
+        self.undone.clear();
+        Ok(outcome)
+    }
+
+    /// Plays `mv` like `act`, but keeps the moves that could be redone, for
+    /// searches that take every move back with `unplay`.
+    pub fn play(&mut self, mv: Move) -> Result<MoveOutcome, IllegalMove> {
+        // the pit should exist under these rules
+        if mv.pit > self.rules.pit_number {
+            return Err(IllegalMove::MalformedCode { code: mv.code() });
+        }
+
+        let hole_index = self.rules.hole_index(mv);
+
+        // the game should not be ended
+        if self.ended {
+            return Err(IllegalMove::GameOver);
+        }
+
+        // the actor should be correct
+        if mv.player != self.actor {
+            return Err(IllegalMove::NotYourTurn { expected: self.actor });
+        }
+
+        // the hole should not be empty
+        if self.board[hole_index] == 0 {
+            return Err(IllegalMove::EmptyPit { pit: mv.pit });
+        }
+
+        Ok(self.sow(mv))
+    }
+
+    /// Takes back the last move, returning it together with what it did.
+    pub fn undo(&mut self) -> Option<(Move, MoveOutcome)> {
+        let (mv, outcome) = self.unplay()?;
+        self.undone.push(mv);
+        Some((mv, outcome))
+    }
+
+    /// Takes back the last move like `undo`, without making it one to redo.
+    pub fn unplay(&mut self) -> Option<(Move, MoveOutcome)> {
+        let entry = self.journal.pop()?;
+
+        // put back the swept seeds
+        if let Some(board) = entry.sweep {
+            self.board = board;
+        }
+
+        // give back the captured seeds
+        if let Some((hole_index, captured)) = entry.capture {
+            self.board[self.rules.score_hole(entry.mv.player)] -= 1 + captured;
+            self.board[hole_index] = 1;
+            self.board[self.rules.opposite_hole(hole_index)] = captured;
+        }
+
+        // pick up the sown seeds along the same path
+        let hole_index = self.rules.hole_index(entry.mv);
+        let mut current_hole = self.next_hole(hole_index);
+        for _ in 0..entry.seeds {
+            if current_hole == self.rules.score_hole(entry.mv.player.opponent()) {
+                current_hole = self.next_hole(current_hole);
+            }
+            self.board[current_hole] -= 1;
+            current_hole = self.next_hole(current_hole);
+        }
+        self.board[hole_index] = entry.seeds;
+
+        self.actor = entry.mv.player;
+        self.ended = false;
+
+        Some((entry.mv, MoveOutcome {
+            extra_turn: entry.extra_turn,
+            capture: entry.capture.is_some(),
+            ended: entry.sweep.is_some(),
+        }))
+    }
+
+    /// Plays again the last undone move.
+    pub fn redo(&mut self) -> Option<(Move, MoveOutcome)> {
+        let mv = self.undone.pop()?;
+        Some((mv, self.sow(mv)))
+    }
+
+    fn sow(&mut self, mv: Move) -> MoveOutcome {
+        let hole_index = self.rules.hole_index(mv);
+        let mut entry = JournalEntry {
+            mv,
+            seeds: self.board[hole_index],
+            capture: None,
+            extra_turn: false,
+            sweep: None,
+        };
+
+        // get all pieces form this hole
+        let mut mancala_pieces = self.board[hole_index];
+        self.board[hole_index] = 0;
+
+        // sow!
+        let mut current_hole = self.next_hole(hole_index);
+        while mancala_pieces > 0 {
+            // if opponent's score hole, skip it
+            if self.is_opponent_score_hole(current_hole) {
+                current_hole = self.next_hole(current_hole);
+            }
+            // else, sow one piece in current hole
+            self.board[current_hole] += 1;
+            mancala_pieces -= 1;
+
+            // if this is the last piece
+            if mancala_pieces == 0 {
+                // move again
+                entry.extra_turn = self.can_move_again(current_hole);
+                // critical hit
+                entry.capture = self.try_critical_hit(current_hole)
+                    .map(|captured| (current_hole, captured));
+            }
+            current_hole = self.next_hole(current_hole);
+        }
+
+        // change actor
+        if !entry.extra_turn {
+            self.actor = self.actor.opponent();
+        }
+
+        entry.sweep = self.try_end();
+
+        let outcome = MoveOutcome {
+            extra_turn: entry.extra_turn,
+            capture: entry.capture.is_some(),
+            ended: self.ended,
+        };
+        self.journal.push(entry);
+        outcome
+    }
+
+    fn next_hole(&self, hole_index: usize) -> usize {
+        (hole_index + 1usize) % self.rules.hole_number()
+    }
+
+    fn is_opponent_score_hole(&self, hole_index: usize) -> bool {
+        hole_index == self.rules.score_hole(self.actor.opponent())
+    }
+
+    fn can_move_again(&self, hole_index: usize) -> bool {
+        hole_index == self.rules.score_hole(self.actor)
+    }
+
+    // returns the seeds taken from the opposite hole
+    fn try_critical_hit(&mut self, hole_index: usize) -> Option<i32> {
+        if self.is_my_six_hole(hole_index) &&
+            self.board[hole_index] == 1 &&
+            (self.board[self.rules.opposite_hole(hole_index)] > 0 || self.rules.empty_capture) {
+            let opposite_hole = self.rules.opposite_hole(hole_index);
+            let captured = self.board[opposite_hole];
+            self.board[self.rules.score_hole(self.actor)] += 1 + captured;
+            self.board[hole_index] = 0;
+            self.board[opposite_hole] = 0;
+            Some(captured)
+        } else {
+            None
+        }
+    }
+
+    fn is_my_six_hole(&self, hole_index: usize) -> bool {
+        self.rules.first_hole(self.actor) <= hole_index &&
+            hole_index < self.rules.score_hole(self.actor)
+    }
+
+    // returns the board before the sweep if the game has just ended
+    fn try_end(&mut self) -> Option<[i32; HOLE_NUMBER]> {
+        let before = self.board;
+        for player in [Player::One, Player::Two] {
+            let first_hole = self.rules.first_hole(player);
+            let pits = first_hole..first_hole + self.rules.pit_number;
+            if self.board[pits].iter().sum::<i32>() == 0 {
+                let owner = player.opponent();
+                let receiver = match self.rules.sweep {
+                    Sweep::ToOwner => owner,
+                    Sweep::ToOpponent => player,
+                };
+                let receiver_score_hole = self.rules.score_hole(receiver);
+                let first_hole = self.rules.first_hole(owner);
+                for i in first_hole..first_hole + self.rules.pit_number {
+                    self.board[receiver_score_hole] += self.board[i];
+                    self.board[i] = 0;
+                }
+                self.ended = true;
+            }
+        }
+        if self.ended {
+            Some(before)
+        } else {
+            None
+        }
+    }
+}
+
+/// Two situations are equal when they have the same position, whatever the
+/// moves that led there.
+impl PartialEq for GameSituation {
+    fn eq(&self, other: &GameSituation) -> bool {
+        self.rules == other.rules &&
+            self.actor == other.actor &&
+            self.board == other.board &&
+            self.ended == other.ended
+    }
+}
+
+impl Eq for GameSituation {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(game_situation: &mut GameSituation, code: i32) -> Result<MoveOutcome, IllegalMove> {
+        game_situation.act(Move::try_from(code).unwrap())
+    }
+
+    #[test]
+    fn test_move_again() {
+        let mut game_situation = GameSituation::new(Player::One);
+        assert_eq!(play(&mut game_situation, 13), Ok(MoveOutcome {
+            extra_turn: true,
+            capture: false,
+            ended: false,
+        }));
+        assert_eq!(game_situation.actor(), Player::One);
+        assert_eq!(game_situation.board(), &[4, 4, 0, 5, 5, 5, 1, 4, 4, 4, 4, 4, 4, 0]);
+    }
+
+    #[test]
+    fn test_critical_hit() {
+        let mut game_situation = GameSituation::from(Player::One, &[
+            1, 1, 0, 0, 0, 0, 0,
+            4, 4, 4, 4, 4, 4, 0
+        ]);
+        assert_eq!(play(&mut game_situation, 12), Ok(MoveOutcome {
+            extra_turn: false,
+            capture: true,
+            ended: false,
+        }));
+        assert_eq!(game_situation.score(Player::One), 5);
+        assert_eq!(game_situation.board()[10], 0);
+        assert_eq!(game_situation.actor(), Player::Two);
+    }
+
+    #[test]
+    fn test_illegal_leaves_board() {
+        let mut game_situation = GameSituation::new(Player::One);
+        assert_eq!(
+            play(&mut game_situation, 21),
+            Err(IllegalMove::NotYourTurn { expected: Player::One })
+        );
+        assert_eq!(game_situation, GameSituation::new(Player::One));
+        play(&mut game_situation, 11).unwrap();
+        play(&mut game_situation, 21).unwrap();
+        assert_eq!(play(&mut game_situation, 11), Err(IllegalMove::EmptyPit { pit: 1 }));
+    }
+
+    #[test]
+    fn test_sweep_when_side_empty() {
+        let mut game_situation = GameSituation::from(Player::One, &[
+            0, 0, 0, 0, 0, 1, 20,
+            1, 2, 3, 4, 5, 6, 6
+        ]);
+        assert_eq!(play(&mut game_situation, 16), Ok(MoveOutcome {
+            extra_turn: true,
+            capture: false,
+            ended: true,
+        }));
+        assert!(game_situation.ended());
+        assert_eq!(game_situation.score(Player::One), 21);
+        assert_eq!(game_situation.score(Player::Two), 27);
+        assert_eq!(play(&mut game_situation, 11), Err(IllegalMove::GameOver));
+    }
+
+    #[test]
+    fn test_undo_redo_restores_positions() {
+        let seq = [
+            11, 21, 12, 13, 25,
+            11, 21, 12, 22, 11,
+            23, 12, 24, 13, 11,
+            26, 12, 25, 11, 26
+        ];
+        let mut game_situation = GameSituation::new(Player::One);
+        let mut positions = vec![game_situation.clone()];
+        let mut outcomes = Vec::new();
+        for code in seq {
+            outcomes.push(play(&mut game_situation, code).unwrap());
+            positions.push(game_situation.clone());
+        }
+        assert!(game_situation.ended());
+        assert!(outcomes.iter().any(|outcome| outcome.capture));
+
+        for i in (0..seq.len()).rev() {
+            let (mv, outcome) = game_situation.undo().unwrap();
+            assert_eq!(mv.code(), seq[i]);
+            assert_eq!(outcome, outcomes[i]);
+            assert_eq!(game_situation, positions[i]);
+        }
+        assert_eq!(game_situation.undo(), None);
+
+        for i in 0..seq.len() {
+            assert_eq!(game_situation.redo(), Some((Move::try_from(seq[i]).unwrap(), outcomes[i])));
+            assert_eq!(game_situation, positions[i + 1]);
+        }
+        assert_eq!(game_situation.redo(), None);
+        assert_eq!(game_situation.history().map(Move::code).collect::<Vec<_>>(), seq);
+    }
+
+    #[test]
+    fn test_act_discards_redo() {
+        let mut game_situation = GameSituation::new(Player::One);
+        play(&mut game_situation, 11).unwrap();
+        game_situation.undo();
+        play(&mut game_situation, 12).unwrap();
+        assert_eq!(game_situation.redo(), None);
+        assert_eq!(game_situation.history().map(Move::code).collect::<Vec<_>>(), [12]);
+    }
+
+    #[test]
+    fn test_play_keeps_redo() {
+        let mut game_situation = GameSituation::new(Player::One);
+        play(&mut game_situation, 13).unwrap();
+        game_situation.undo();
+        game_situation.play(Move::new(Player::One, 1)).unwrap();
+        game_situation.unplay();
+        assert_eq!(game_situation, GameSituation::new(Player::One));
+        assert_eq!(game_situation.redo().map(|(mv, _)| mv.code()), Some(13));
+        assert_eq!(game_situation.redo(), None);
+    }
+
+    #[test]
+    fn test_small_board() {
+        let mut game_situation = GameSituation::with_rules(RuleSet::kalah(4, 3), Player::One);
+        assert_eq!(game_situation.board(), &[3, 3, 3, 3, 0, 3, 3, 3, 3, 0]);
+        assert_eq!(play(&mut game_situation, 12), Ok(MoveOutcome {
+            extra_turn: true,
+            capture: false,
+            ended: false,
+        }));
+        assert_eq!(game_situation.board(), &[3, 0, 4, 4, 1, 3, 3, 3, 3, 0]);
+        assert_eq!(
+            play(&mut game_situation, 15),
+            Err(IllegalMove::MalformedCode { code: 15 })
+        );
+        play(&mut game_situation, 14).unwrap();
+        assert_eq!(game_situation.board(), &[3, 0, 4, 0, 2, 4, 4, 4, 3, 0]);
+        game_situation.undo();
+        game_situation.undo();
+        assert_eq!(game_situation, GameSituation::with_rules(RuleSet::kalah(4, 3), Player::One));
+    }
+
+    #[test]
+    fn test_empty_capture() {
+        let status = [
+            1, 0, 0, 0, 0, 0, 0,
+            4, 4, 4, 4, 0, 4, 0
+        ];
+        let mut game_situation = GameSituation::from(Player::One, &status);
+        assert_eq!(play(&mut game_situation, 11).map(|outcome| outcome.capture), Ok(false));
+
+        let rules = RuleSet { empty_capture: true, ..RuleSet::default() };
+        let mut game_situation = GameSituation::from_with_rules(rules, Player::One, &status);
+        assert_eq!(play(&mut game_situation, 11), Ok(MoveOutcome {
+            extra_turn: false,
+            capture: true,
+            ended: true,
+        }));
+        assert_eq!(game_situation.score(Player::One), 1);
+        game_situation.undo();
+        assert_eq!(game_situation.board(), &status);
+    }
+
+    #[test]
+    fn test_sweep_to_opponent() {
+        let rules = RuleSet { sweep: Sweep::ToOpponent, ..RuleSet::default() };
+        let mut game_situation = GameSituation::from_with_rules(rules, Player::One, &[
+            0, 0, 0, 0, 0, 1, 20,
+            1, 2, 3, 4, 5, 6, 6
+        ]);
+        assert!(play(&mut game_situation, 16).unwrap().ended);
+        assert_eq!(game_situation.score(Player::One), 42);
+        assert_eq!(game_situation.score(Player::Two), 6);
+    }
+}
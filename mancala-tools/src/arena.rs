@@ -0,0 +1,332 @@
+//! Matches between engines that decide like `mancala_operator`, scored the
+//! way the course ranks T3.2: net seeds, and the efficiency n²/t of net seeds
+//! n over the milliseconds t spent deciding.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Instant;
+
+use mancala_core::{GameSituation, Move, Player, RuleSet};
+use t3_2_rust::difficulty::{Difficulty, Level};
+use t3_2_rust::eval::{DEFAULT_WEIGHTS, FEATURE_NUMBER};
+use t3_2_rust::mcts::MctsConfig;
+use t3_2_rust::rng::Rng;
+use t3_2_rust::search::ordered_moves;
+use t3_2_rust::{operate, set_evaluator_weights, set_opening_book};
+
+use crate::protocol::ProcessEngine;
+use crate::selfplay::OperatorConfig;
+
+// milliseconds per decision, as `mancala_operator` takes
+const TIME_BUDGET: u32 = 300;
+
+/// Anything that picks a move code for player `flag` on a 14-hole `status`.
+pub trait Engine {
+    fn name(&self) -> &str;
+
+    /// The move code to play, or why there is none. Either way an illegal
+    /// answer loses the game by the penalty rule.
+    fn decide(&mut self, flag: i32, status: &[i32]) -> Result<i32, String>;
+}
+
+/// The operator of `t3_2_rust`, with its own weights and opening book
+/// setting.
+pub struct Operator {
+    name: String,
+    engine: t3_2_rust::Engine,
+    time_budget: u32,
+    weights: [f32; FEATURE_NUMBER],
+    book: bool,
+}
+
+impl Engine for Operator {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn decide(&mut self, flag: i32, status: &[i32]) -> Result<i32, String> {
+        // the settings are shared by every operator of the thread
+        set_evaluator_weights(&self.weights)?;
+        set_opening_book(self.book);
+        Ok(operate(RuleSet::default(), self.engine, flag, status, self.time_budget))
+    }
+}
+
+/// A search of fixed depth, as `mancala-tune` plays.
+pub struct FixedDepth {
+    name: String,
+    config: OperatorConfig,
+}
+
+impl Engine for FixedDepth {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn decide(&mut self, flag: i32, status: &[i32]) -> Result<i32, String> {
+        let mut game_situation = GameSituation::from(Player::from_flag(flag), status);
+        Ok(self.config.choose(&mut game_situation).code())
+    }
+}
+
+/// Any legal move.
+pub struct RandomMover {
+    name: String,
+    rng: Rng,
+}
+
+impl Engine for RandomMover {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn decide(&mut self, flag: i32, status: &[i32]) -> Result<i32, String> {
+        let game_situation = GameSituation::from(Player::from_flag(flag), status);
+        let moves = ordered_moves(&game_situation, None).collect::<Vec<_>>();
+        if moves.is_empty() {
+            return Err("no legal move".to_string());
+        }
+        Ok(moves[self.rng.below(moves.len())].code())
+    }
+}
+
+/// An engine from its description, a kind and options after a colon, e.g.
+/// `operator:ms=100,book=off`, `fixed:depth=6`, `mcts:playouts=5000`, `easy`
+/// or `random:seed=3`. Every kind but `random` takes `weights=a/b/c/d/e/f`.
+/// `exec:COMMAND` runs an engine speaking the protocol of
+/// `crate::protocol`, and `wasm:PATH` loads a `*_bg.wasm` build.
+pub fn parse_engine(spec: &str) -> Result<Box<dyn Engine>, String> {
+    if let Some(command_line) = spec.strip_prefix("exec:") {
+        return Ok(Box::new(ProcessEngine::spawn(command_line, TIME_BUDGET)?));
+    }
+    if let Some(path) = spec.strip_prefix("wasm:") {
+        #[cfg(feature = "wasm")]
+        return Ok(Box::new(crate::wasm::WasmEngine::load(path)?));
+        #[cfg(not(feature = "wasm"))]
+        return Err(format!("{}: built without the wasm feature", path));
+    }
+    let (kind, options) = spec.split_once(':').unwrap_or((spec, ""));
+    let mut time_budget = TIME_BUDGET;
+    let mut weights = DEFAULT_WEIGHTS;
+    let mut book = true;
+    let mut depth = 4;
+    let mut seed = 1;
+    let mut mcts = MctsConfig::default();
+    for option in options.split(',').filter(|option| !option.is_empty()) {
+        let (key, value) = option.split_once('=').ok_or_else(|| format!("{}: expected key=value, got {}", spec, option))?;
+        let invalid = || format!("{}: invalid value for {}: {}", spec, key, value);
+        match key {
+            "ms" => time_budget = value.parse().map_err(|_| invalid())?,
+            "weights" => {
+                let values = value.split('/').map(str::parse).collect::<Result<Vec<f32>, _>>().map_err(|_| invalid())?;
+                weights = values.try_into().map_err(|_| invalid())?;
+            }
+            "book" => {
+                book = match value {
+                    "on" | "true" | "1" => true,
+                    "off" | "false" | "0" => false,
+                    _ => return Err(format!("{}: bad book value {}", spec, value)),
+                }
+            }
+            "depth" => depth = value.parse().map_err(|_| invalid())?,
+            "seed" => seed = value.parse().map_err(|_| invalid())?,
+            "playouts" => mcts.playouts = value.parse().map_err(|_| invalid())?,
+            "exploration" => mcts.exploration = value.parse().map_err(|_| invalid())?,
+            "rollout" => mcts.rollout = value.parse()?,
+            _ => return Err(format!("{}: unknown option {}", spec, key)),
+        }
+    }
+
+    let name = spec.to_string();
+    let operator = |engine| Operator { name: spec.to_string(), engine, time_budget, weights, book };
+    Ok(match kind {
+        "operator" => Box::new(operator(t3_2_rust::Engine::AlphaBeta)),
+        "mcts" => Box::new(operator(t3_2_rust::Engine::MonteCarlo(MctsConfig { seed, ..mcts }))),
+        "fixed" => Box::new(FixedDepth { name, config: OperatorConfig { weights, depth } }),
+        "random" => Box::new(RandomMover { name, rng: Rng::new(seed) }),
+        _ => {
+            let level = kind.parse::<Level>().map_err(|_| format!("unknown engine `{}`", kind))?;
+            match level.difficulty() {
+                Some(difficulty) => Box::new(operator(t3_2_rust::Engine::Handicapped(Difficulty { seed, ..difficulty }))),
+                None => Box::new(operator(t3_2_rust::Engine::AlphaBeta)),
+            }
+        }
+    })
+}
+
+/// How one game went.
+#[derive(Clone, Debug)]
+pub struct GameRecord {
+    /// Player one's final score minus player two's, penalties included.
+    pub difference: i32,
+    /// Milliseconds each player spent deciding.
+    pub time: [f64; 2],
+    pub decisions: [u32; 2],
+    /// The longest decision of each player in milliseconds.
+    pub slowest: [f64; 2],
+    /// The player who answered with an illegal move, and why.
+    pub forfeit: Option<(Player, String)>,
+}
+
+/// Plays `opening` to the end, `one` deciding for player one and `two` for
+/// player two. An illegal move ends the game at once: the offender scores
+/// the seeds of their score hole and the opponent all the others.
+pub fn play_game(opening: &GameSituation, one: &mut dyn Engine, two: &mut dyn Engine) -> GameRecord {
+    let mut game_situation = opening.clone();
+    let mut record = GameRecord { difference: 0, time: [0.0; 2], decisions: [0; 2], slowest: [0.0; 2], forfeit: None };
+    while !game_situation.ended() {
+        let actor = game_situation.actor();
+        let engine: &mut dyn Engine = if actor == Player::One { &mut *one } else { &mut *two };
+        let side = actor.number() as usize - 1;
+
+        let start = Instant::now();
+        let decision = engine.decide(actor.number(), game_situation.board());
+        let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+        record.time[side] += elapsed;
+        record.decisions[side] += 1;
+        record.slowest[side] = record.slowest[side].max(elapsed);
+
+        let result = decision.and_then(|code| {
+            let mv = Move::try_from(code).map_err(|error| format!("{}: {}", code, error))?;
+            game_situation.act(mv).map_err(|error| format!("{}: {}", code, error))
+        });
+        if let Err(reason) = result {
+            let total_seeds = game_situation.rules().total_seeds();
+            let kept = game_situation.score(actor);
+            let lead = 2 * kept - total_seeds;
+            record.difference = if actor == Player::One { lead } else { -lead };
+            record.forfeit = Some((actor, reason));
+            return record;
+        }
+    }
+    record.difference = game_situation.score(Player::One) - game_situation.score(Player::Two);
+    record
+}
+
+/// Which engines meet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Every engine against every other.
+    RoundRobin,
+    /// The first engine against each of the others.
+    Gauntlet,
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Mode, String> {
+        match s {
+            "round-robin" => Ok(Mode::RoundRobin),
+            "gauntlet" => Ok(Mode::Gauntlet),
+            _ => Err(format!("unknown mode `{}`", s)),
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Mode::RoundRobin => "round-robin",
+            Mode::Gauntlet => "gauntlet",
+        })
+    }
+}
+
+/// The pairs of engine indices that meet among `engines` engines.
+pub fn pairings(mode: Mode, engines: usize) -> Vec<(usize, usize)> {
+    match mode {
+        Mode::RoundRobin => (0..engines).flat_map(|a| (a + 1..engines).map(move |b| (a, b))).collect(),
+        Mode::Gauntlet => (1..engines).map(|b| (0, b)).collect(),
+    }
+}
+
+/// The games of one engine added up.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Standing {
+    /// Net seeds.
+    pub net: i32,
+    /// Wins, draws and losses.
+    pub results: [u32; 3],
+    /// Milliseconds spent deciding.
+    pub time: f64,
+    pub decisions: u32,
+    /// The longest decision in milliseconds.
+    pub slowest: f64,
+    /// Games lost by an illegal move.
+    pub forfeits: u32,
+}
+
+impl Standing {
+    /// Adds `game` as played by `player`.
+    pub fn record(&mut self, game: &GameRecord, player: Player) {
+        let side = player.number() as usize - 1;
+        let difference = if player == Player::One { game.difference } else { -game.difference };
+        self.net += difference;
+        self.results[if difference > 0 { 0 } else if difference == 0 { 1 } else { 2 }] += 1;
+        self.time += game.time[side];
+        self.decisions += game.decisions[side];
+        self.slowest = self.slowest.max(game.slowest[side]);
+        if game.forfeit.as_ref().is_some_and(|(offender, _)| *offender == player) {
+            self.forfeits += 1;
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.results.iter().sum()
+    }
+
+    /// Milliseconds per decision.
+    pub fn average_time(&self) -> f64 {
+        self.time / self.decisions.max(1) as f64
+    }
+
+    /// n²/t, which the course ranks the engines of positive net seeds by,
+    /// `None` for the others.
+    pub fn efficiency(&self) -> Option<f64> {
+        (self.net > 0).then(|| (self.net as f64).powi(2) / self.time.max(f64::MIN_POSITIVE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_play_game() {
+        let mut fixed = parse_engine("fixed:depth=2").unwrap();
+        let mut random = parse_engine("random:seed=5").unwrap();
+        let game = play_game(&GameSituation::new(Player::One), fixed.as_mut(), random.as_mut());
+        assert!(game.forfeit.is_none());
+        assert!(game.decisions[0] > 0 && game.decisions[1] > 0);
+
+        let mut standings = [Standing::default(); 2];
+        standings[0].record(&game, Player::One);
+        standings[1].record(&game, Player::Two);
+        assert_eq!(standings[0].net, -standings[1].net);
+        assert_eq!(standings[0].results, [standings[1].results[2], standings[1].results[1], standings[1].results[0]]);
+
+        // answering with the opponent's pit forfeits the game
+        struct Cheater;
+        impl Engine for Cheater {
+            fn name(&self) -> &str {
+                "cheater"
+            }
+
+            fn decide(&mut self, flag: i32, _: &[i32]) -> Result<i32, String> {
+                Ok((3 - flag) * 10 + 1)
+            }
+        }
+        let game = play_game(&GameSituation::new(Player::One), fixed.as_mut(), &mut Cheater);
+        assert_eq!(game.forfeit.map(|(offender, _)| offender), Some(Player::Two));
+        // player two's score hole was still empty, so player one gets all 48
+        assert_eq!(game.difference, 48);
+
+        assert!(parse_engine("operator:ms=abc").is_err());
+        assert!(parse_engine("grandmaster").is_err());
+        assert!(parse_engine("operator:book=off").is_ok());
+        assert_eq!(parse_engine("operator:book=yes").err(), Some("operator:book=yes: bad book value yes".to_string()));
+        assert_eq!(pairings(Mode::RoundRobin, 3), [(0, 1), (0, 2), (1, 2)]);
+        assert_eq!(pairings(Mode::Gauntlet, 3), [(0, 1), (0, 2)]);
+    }
+}
@@ -0,0 +1,157 @@
+//! Plays engines against each other from random openings, every opening with
+//! both colours, in place of the two games of `T3/test.js`.
+//!
+//! ```text
+//! cargo run --release -p mancala-tools --bin mancala-arena -- [--mode round-robin|gauntlet]
+//!     [--openings 10] [--plies 4] [--seed 1] [--suite openings.txt] [--sprt ELO0,ELO1 [--alpha 0.05] [--beta 0.05]]
+//!     ENGINE ENGINE...
+//! ```
+//!
+//! An engine is a kind with options, e.g. `operator`, `operator:ms=100,book=off`,
+//! `fixed:depth=6,weights=1/0/0/0/0/0`, `mcts:playouts=5000,rollout=random`,
+//! `easy:seed=2` or `random`, `exec:COMMAND` for an executable speaking the
+//! protocol of `mancala-engine`, or `wasm:PATH` for a `*_bg.wasm` from
+//! `wasm-pack build`, whose decisions stop at 2000 ms. The gauntlet plays the first engine against
+//! each of the others.
+//!
+//! The openings are `--openings` random ones of `--plies` moves, or those of
+//! a suite file written by `mancala-openings`.
+//!
+//! With `--sprt` the arena tests whether the first of two engines is `ELO1`
+//! (BayesElo) stronger than the second rather than `ELO0`, stopping as soon
+//! as the games tell, or after the last opening. It exits with 0 if the
+//! first engine passed, and 1 if it failed or the test stayed undecided.
+
+use std::process::exit;
+use std::str::FromStr;
+
+use mancala_core::Player;
+use mancala_tools::arena::{pairings, parse_engine, play_game, Mode, Standing};
+use mancala_tools::rating::{estimate, Sprt, Verdict};
+use mancala_tools::selfplay::random_opening;
+use mancala_tools::suite::parse_suite;
+use t3_2_rust::rng::Rng;
+
+// the course disqualifies any decision slower than this
+const TIME_LIMIT: f64 = 2000.0;
+
+fn main() {
+    let mut mode = Mode::RoundRobin;
+    let mut openings: usize = 10;
+    let mut plies: usize = 4;
+    let mut seed: u64 = 1;
+    let mut suite = None;
+    let mut sprt = None;
+    let (mut alpha, mut beta) = (0.05, 0.05);
+    let mut engines = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            engines.push(parse_engine(&arg).unwrap_or_else(|error| fail(&error)));
+            continue;
+        }
+        let value = args.next().unwrap_or_else(|| fail(&format!("{} needs a value", arg)));
+        match arg.as_str() {
+            "--mode" => mode = value.parse().unwrap_or_else(|error: String| fail(&error)),
+            "--openings" => openings = parse(&arg, &value),
+            "--plies" => plies = parse(&arg, &value),
+            "--seed" => seed = parse(&arg, &value),
+            "--suite" => {
+                let text = std::fs::read_to_string(&value).unwrap_or_else(|error| fail(&format!("cannot read {}: {}", value, error)));
+                suite = Some(parse_suite(&text).unwrap_or_else(|error| fail(&format!("{}: {}", value, error))));
+            }
+            "--sprt" => {
+                let (elo0, elo1) = value.split_once(',').unwrap_or_else(|| fail("--sprt needs ELO0,ELO1"));
+                sprt = Some(Sprt::new(parse(&arg, elo0), parse(&arg, elo1)));
+            }
+            "--alpha" => alpha = parse(&arg, &value),
+            "--beta" => beta = parse(&arg, &value),
+            _ => fail(&format!("unknown option {}", arg)),
+        }
+    }
+    if engines.len() < 2 {
+        fail("needs at least two engines");
+    }
+    let sprt = sprt.map(|sprt| Sprt { alpha, beta, ..sprt });
+    if sprt.is_some() && engines.len() != 2 {
+        fail("--sprt needs exactly two engines");
+    }
+
+    let openings = suite.unwrap_or_else(|| {
+        let mut rng = Rng::new(seed);
+        (0..openings).map(|_| random_opening(&mut rng, plies)).collect()
+    });
+    let mut standings = vec![Standing::default(); engines.len()];
+    let mut verdict = Verdict::Continue;
+    for (a, b) in pairings(mode, engines.len()) {
+        let mut pairing = Standing::default();
+        for opening in &openings {
+            if let Some(sprt) = sprt {
+                verdict = sprt.verdict(pairing.results);
+                if verdict != Verdict::Continue {
+                    break;
+                }
+            }
+            for a_moves_first in [true, false] {
+                let [engine_a, engine_b] = engines.get_disjoint_mut([a, b]).unwrap();
+                let (one, two) = if a_moves_first { (engine_a, engine_b) } else { (engine_b, engine_a) };
+                let game = play_game(opening, one.as_mut(), two.as_mut());
+                if let Some((offender, reason)) = &game.forfeit {
+                    let name = if (*offender == Player::One) == a_moves_first { engines[a].name() } else { engines[b].name() };
+                    eprintln!("{} forfeits with an illegal move: {}", name, reason);
+                }
+                let (a_player, b_player) = if a_moves_first { (Player::One, Player::Two) } else { (Player::Two, Player::One) };
+                pairing.record(&game, a_player);
+                standings[a].record(&game, a_player);
+                standings[b].record(&game, b_player);
+            }
+        }
+        if let Some(sprt) = sprt {
+            verdict = sprt.verdict(pairing.results);
+        }
+        let [wins, draws, losses] = pairing.results;
+        println!(
+            "{} vs {}: net {:+} seeds, {} wins, {} draws, {} losses, Elo {}",
+            engines[a].name(),
+            engines[b].name(),
+            pairing.net,
+            wins,
+            draws,
+            losses,
+            estimate(pairing.results).map_or("-".to_string(), |estimate| estimate.to_string()),
+        );
+        if let Some(sprt) = sprt {
+            let (lower, upper) = sprt.bounds();
+            println!("SPRT: LLR {:.3} in ({:.3}, {:.3}), {:?}", sprt.llr(pairing.results), lower, upper, verdict);
+        }
+    }
+
+    println!();
+    println!("{:<32} {:>6} {:>6} {:>12} {:>9} {:>9} {:>10}", "engine", "games", "net", "W/D/L", "avg ms", "max ms", "n²/t");
+    for (engine, standing) in engines.iter().zip(&standings) {
+        let [wins, draws, losses] = standing.results;
+        println!(
+            "{:<32} {:>6} {:>+6} {:>12} {:>9.2} {:>9.1} {:>10}{}",
+            engine.name(),
+            standing.games(),
+            standing.net,
+            format!("{}/{}/{}", wins, draws, losses),
+            standing.average_time(),
+            standing.slowest,
+            standing.efficiency().map_or("-".to_string(), |efficiency| format!("{:.3}", efficiency)),
+            if standing.slowest > TIME_LIMIT { "  over the 2000 ms limit" } else { "" },
+        );
+    }
+    if sprt.is_some() && verdict != Verdict::Accept {
+        exit(1);
+    }
+}
+
+fn parse<T: FromStr>(option: &str, value: &str) -> T {
+    value.parse().unwrap_or_else(|_| fail(&format!("invalid value for {}: {}", option, value)))
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("mancala-arena: {}", message);
+    exit(2);
+}
@@ -0,0 +1,92 @@
+//! Compares the serial search of `mancala_operator` with the parallel one of
+//! the `parallel` feature: nodes and milliseconds to reach the same depth
+//! from random openings.
+//!
+//! ```text
+//! cargo run --release -p mancala-tools --features parallel --bin mancala-bench -- [--positions 20]
+//!     [--plies 6] [--depth 12] [--seed 1]
+//! ```
+//!
+//! `RAYON_NUM_THREADS` sets the threads of the parallel search.
+
+use std::process::exit;
+use std::str::FromStr;
+
+fn main() {
+    let mut positions: usize = 20;
+    let mut plies: usize = 6;
+    let mut depth: i32 = 12;
+    let mut seed: u64 = 1;
+    let mut args = std::env::args().skip(1);
+    while let Some(option) = args.next() {
+        let value = args.next().unwrap_or_else(|| fail(&format!("{} needs a value", option)));
+        match option.as_str() {
+            "--positions" => positions = parse(&option, &value),
+            "--plies" => plies = parse(&option, &value),
+            "--depth" => depth = parse(&option, &value),
+            "--seed" => seed = parse(&option, &value),
+            _ => fail(&format!("unknown option {}", option)),
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    compare(positions, plies, depth, seed);
+    #[cfg(not(feature = "parallel"))]
+    {
+        let _ = (positions, plies, depth, seed);
+        fail("built without the parallel feature");
+    }
+}
+
+#[cfg(feature = "parallel")]
+fn compare(positions: usize, plies: usize, depth: i32, seed: u64) {
+    use mancala_tools::selfplay::random_opening;
+    use t3_2_rust::eval::FeatureEvaluator;
+    use t3_2_rust::rng::Rng;
+    use t3_2_rust::search::Search;
+
+    let mut rng = Rng::new(seed);
+    let mut total = [(0, 0.0); 2];
+    println!("{:>8} {:>12} {:>10} {:>12} {:>10}", "position", "serial nodes", "serial ms", "parallel", "ms");
+    for i in 1..=positions {
+        let mut game_situation = random_opening(&mut rng, plies);
+
+        let mut serial = Search::with_evaluator(FeatureEvaluator::default());
+        let (_, serial_value) = serial.iterative_deepening(&mut game_situation, depth, f64::INFINITY).unwrap();
+        let mut parallel = Search::with_evaluator(FeatureEvaluator::default());
+        let (_, parallel_value) = parallel.iterative_deepening_parallel(&game_situation, depth, f64::INFINITY).unwrap();
+        if serial_value != parallel_value {
+            fail(&format!("position {}: the searches disagree, {} against {}", i, serial_value, parallel_value));
+        }
+
+        println!(
+            "{:>8} {:>12} {:>10.1} {:>12} {:>10.1}",
+            i, serial.stats.nodes, serial.stats.elapsed_ms, parallel.stats.nodes, parallel.stats.elapsed_ms
+        );
+        for (total, stats) in total.iter_mut().zip([serial.stats, parallel.stats]) {
+            total.0 += stats.nodes;
+            total.1 += stats.elapsed_ms;
+        }
+    }
+    let [(serial_nodes, serial_ms), (parallel_nodes, parallel_ms)] = total;
+    println!(
+        "parallel/serial: {:.2}x the nodes in {:.2}x the time, on {} threads",
+        parallel_nodes as f64 / serial_nodes as f64,
+        parallel_ms / serial_ms,
+        rayon_threads(),
+    );
+}
+
+#[cfg(feature = "parallel")]
+fn rayon_threads() -> String {
+    std::env::var("RAYON_NUM_THREADS").unwrap_or_else(|_| "all".to_string())
+}
+
+fn parse<T: FromStr>(option: &str, value: &str) -> T {
+    value.parse().unwrap_or_else(|_| fail(&format!("invalid value for {}: {}", option, value)))
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("mancala-bench: {}", message);
+    exit(2);
+}
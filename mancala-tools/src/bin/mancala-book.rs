@@ -0,0 +1,77 @@
+//! Builds the opening book of `mancala_operator`.
+//!
+//! ```text
+//! cargo run --release -p mancala-tools --bin mancala-book -- [--plies 4] [--depth 12] [--out PATH]
+//! ```
+//!
+//! Every standard Kalah position reachable in fewer than `--plies` moves,
+//! with either player moving first, is searched `--depth` turns deep with the
+//! embedded evaluation weights. The best moves are written to `--out`, by
+//! default the book `t3_2_rust` embeds at build time.
+
+use std::process::exit;
+use std::time::Instant;
+
+use mancala_core::{GameSituation, Player};
+use t3_2_rust::book::OpeningBook;
+use t3_2_rust::eval::FeatureEvaluator;
+use t3_2_rust::search::{ordered_moves, Search};
+
+const BOOK_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../T3/t3_2_rust/src/book.txt");
+
+fn main() {
+    let mut plies = 4;
+    let mut depth = 12;
+    let mut out = BOOK_FILE.to_string();
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args.next().unwrap_or_else(|| fail(&format!("{} needs a value", flag)));
+        let invalid = || fail(&format!("invalid value for {}: {}", flag, value));
+        match flag.as_str() {
+            "--plies" => plies = value.parse().unwrap_or_else(|_| invalid()),
+            "--depth" => depth = value.parse().unwrap_or_else(|_| invalid()),
+            "--out" => out = value,
+            _ => fail(&format!("unknown option {}", flag)),
+        }
+    }
+
+    let start = Instant::now();
+    let mut book = OpeningBook::new();
+    let mut search = Search::with_evaluator(FeatureEvaluator::default());
+    let mut frontier = vec![GameSituation::new(Player::One), GameSituation::new(Player::Two)];
+    for ply in 0..plies {
+        let mut next = Vec::new();
+        for mut game_situation in frontier {
+            if book.lookup(&game_situation).is_some() {
+                continue;
+            }
+            let Some((mv, _)) = search.best_move(&mut game_situation, depth) else {
+                continue;
+            };
+            book.insert(&game_situation, mv).unwrap_or_else(|error| fail(&error));
+            for mv in ordered_moves(&game_situation, None) {
+                let mut child = game_situation.clone();
+                child.act(mv).unwrap();
+                next.push(child);
+            }
+        }
+        eprintln!("ply {}: {} positions in {:.1?}", ply, book.len(), start.elapsed());
+        frontier = next;
+    }
+
+    let text = format!(
+        "# written by `mancala-book` in /mancala-tools, {} plies searched {} turns deep\n\
+         # player to move, the 14 holes of the board, the move\n{}",
+        plies, depth, book,
+    );
+    if let Err(error) = std::fs::write(&out, text) {
+        eprintln!("mancala-book: cannot write {}: {}", out, error);
+        exit(1);
+    }
+    eprintln!("wrote {}", out);
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("mancala-book: {}", message);
+    exit(2);
+}
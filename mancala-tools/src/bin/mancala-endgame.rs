@@ -0,0 +1,50 @@
+//! Generates the endgame table of standard Kalah positions.
+//!
+//! ```text
+//! cargo run --release -p mancala-tools --bin mancala-endgame -- [--seeds 12] [--out endgame.bin]
+//! ```
+//!
+//! The table holds every position with at most `--seeds` seeds in the pits,
+//! two bytes per distribution of the seeds: 1.3 MB for 10 seeds, 5.4 MB for
+//! 12, 60 MB for 16. Native searches can load it with
+//! `EndgameTable::from_vec`. The wasm build embeds its own, smaller table
+//! with the `endgame-table` feature of `t3_2_rust`.
+
+use std::process::exit;
+use std::time::Instant;
+
+use t3_2_rust::endgame::{EndgameTable, MAX_TABLE_SEEDS};
+
+fn main() {
+    let mut seeds = 12;
+    let mut out = "endgame.bin".to_string();
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args.next().unwrap_or_else(|| fail(&format!("{} needs a value", flag)));
+        match flag.as_str() {
+            "--seeds" => {
+                seeds = value
+                    .parse()
+                    .ok()
+                    .filter(|&seeds| seeds <= MAX_TABLE_SEEDS)
+                    .unwrap_or_else(|| fail(&format!("--seeds must be at most {}", MAX_TABLE_SEEDS)));
+            }
+            "--out" => out = value,
+            _ => fail(&format!("unknown option {}", flag)),
+        }
+    }
+
+    let start = Instant::now();
+    let bytes = EndgameTable::generate(seeds).to_bytes();
+    eprintln!("solved every position of up to {} seeds in {:.1?}", seeds, start.elapsed());
+    if let Err(error) = std::fs::write(&out, &bytes) {
+        eprintln!("mancala-endgame: cannot write {}: {}", out, error);
+        exit(1);
+    }
+    eprintln!("wrote {} bytes to {}", bytes.len(), out);
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("mancala-endgame: {}", message);
+    exit(2);
+}
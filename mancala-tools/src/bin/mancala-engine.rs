@@ -0,0 +1,44 @@
+//! `mancala_operator` behind the line protocol of `mancala_tools::protocol`,
+//! for `mancala-arena exec:PATH` and the arenas of other teams.
+//!
+//! ```text
+//! cargo run --release -p mancala-tools --bin mancala-engine
+//! ```
+
+use std::io::BufRead;
+
+use mancala_core::RuleSet;
+use mancala_tools::protocol::{parse_command, Command};
+use t3_2_rust::{operate, search_stats, Engine};
+
+// milliseconds per decision when `go` gives no movetime, as the operator
+const DEFAULT_MOVETIME: u32 = 300;
+
+fn main() {
+    let mut position = None;
+    for line in std::io::stdin().lock().lines().map_while(Result::ok) {
+        match parse_command(&line) {
+            Ok(Some(Command::Mancala)) => {
+                println!("id name mancala-engine {}", env!("CARGO_PKG_VERSION"));
+                println!("mancalaok");
+            }
+            Ok(Some(Command::IsReady)) => println!("readyok"),
+            Ok(Some(Command::Position(game_situation))) => position = Some(game_situation),
+            Ok(Some(Command::Go { movetime })) => {
+                let Some(game_situation) = &position else {
+                    println!("info string no position");
+                    continue;
+                };
+                let flag = game_situation.actor().number();
+                let movetime = movetime.unwrap_or(DEFAULT_MOVETIME);
+                let code = operate(RuleSet::default(), Engine::AlphaBeta, flag, game_situation.board(), movetime);
+                let stats = search_stats();
+                println!("info seldepth {} nodes {} time {:.0}", stats[4], stats[0], stats[5]);
+                println!("bestmove {}", code);
+            }
+            Ok(Some(Command::Quit)) => break,
+            Ok(None) => {}
+            Err(error) => println!("info string {}", error),
+        }
+    }
+}
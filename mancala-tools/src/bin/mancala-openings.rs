@@ -0,0 +1,64 @@
+//! Generates an opening suite for `mancala-arena --suite`.
+//!
+//! ```text
+//! cargo run --release -p mancala-tools --bin mancala-openings -- [--count 50] [--random 4] [--book 0]
+//!     [--depth 8] [--margin 2] [--seed 1] [--out openings.txt]
+//! ```
+//!
+//! Every opening plays `--random` random moves from the standard start, then
+//! up to `--book` moves of the embedded opening book, and is kept if a search
+//! of `--depth` turns values it within `--margin` seeds of even.
+
+use std::process::exit;
+use std::str::FromStr;
+
+use mancala_tools::suite::{format_suite, generate, SuiteConfig};
+use t3_2_rust::rng::Rng;
+
+fn main() {
+    let mut config = SuiteConfig { count: 50, random_plies: 4, book_plies: 0, depth: 8, margin: 2.0 };
+    let mut seed: u64 = 1;
+    let mut out = "openings.txt".to_string();
+    let mut args = std::env::args().skip(1);
+    while let Some(option) = args.next() {
+        let value = args.next().unwrap_or_else(|| fail(&format!("{} needs a value", option)));
+        match option.as_str() {
+            "--count" => config.count = parse(&option, &value),
+            "--random" => config.random_plies = parse(&option, &value),
+            "--book" => config.book_plies = parse(&option, &value),
+            "--depth" => config.depth = parse(&option, &value),
+            "--margin" => config.margin = parse(&option, &value),
+            "--seed" => seed = parse(&option, &value),
+            "--out" => out = value,
+            _ => fail(&format!("unknown option {}", option)),
+        }
+    }
+
+    let openings = generate(&config, &mut Rng::new(seed));
+    if openings.len() < config.count {
+        eprintln!("only {} openings are balanced within {} seeds", openings.len(), config.margin);
+    }
+    let mut text = format!(
+        "# {} openings: {} random and {} book moves, within {} seeds at depth {}\n",
+        openings.len(),
+        config.random_plies,
+        config.book_plies,
+        config.margin,
+        config.depth
+    );
+    text.push_str(&format_suite(&openings));
+    if let Err(error) = std::fs::write(&out, text) {
+        eprintln!("mancala-openings: cannot write {}: {}", out, error);
+        exit(1);
+    }
+    eprintln!("wrote {}", out);
+}
+
+fn parse<T: FromStr>(option: &str, value: &str) -> T {
+    value.parse().unwrap_or_else(|_| fail(&format!("invalid value for {}: {}", option, value)))
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("mancala-openings: {}", message);
+    exit(2);
+}
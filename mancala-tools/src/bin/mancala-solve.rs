@@ -0,0 +1,74 @@
+//! Solves a Kalah position: its value under perfect play and a best move.
+//!
+//! ```text
+//! cargo run --release -p mancala-tools --bin mancala-solve -- [--rules kalah(4,3)] [--flag 1]
+//!     [--status 4,4,4,4,0,4,4,4,4,0] [--nodes 1000000000] [--tt-bits 22] [--endgame endgame.bin]
+//! ```
+//!
+//! `--status` defaults to the initial board of `--rules`. `--endgame` loads a
+//! table written by `mancala-endgame`, which only helps standard Kalah.
+
+use std::process::exit;
+use std::str::FromStr;
+use std::time::Instant;
+
+use mancala_core::{GameSituation, Player, RuleSet};
+use t3_2_rust::endgame::EndgameTable;
+use t3_2_rust::search::Search;
+use t3_2_rust::solver::solve;
+
+fn main() {
+    let mut rules = RuleSet::default();
+    let mut flag = 1;
+    let mut status = None;
+    let mut max_nodes: u64 = 1_000_000_000;
+    let mut tt_bits: u32 = 22;
+    let mut endgame = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(option) = args.next() {
+        let value = args.next().unwrap_or_else(|| fail(&format!("{} needs a value", option)));
+        match option.as_str() {
+            "--rules" => rules = value.parse().unwrap_or_else(|error| fail(&format!("{}", error))),
+            "--flag" => flag = parse(&option, &value),
+            "--status" => status = Some(value.split(',').map(|seeds| parse(&option, seeds.trim())).collect::<Vec<i32>>()),
+            "--nodes" => max_nodes = parse(&option, &value),
+            "--tt-bits" => tt_bits = parse::<u32>(&option, &value).min(30),
+            "--endgame" => {
+                let bytes = std::fs::read(&value).unwrap_or_else(|error| fail(&format!("cannot read {}: {}", value, error)));
+                let table = EndgameTable::from_vec(bytes).unwrap_or_else(|| fail(&format!("{} is not an endgame table", value)));
+                endgame = Some(&*Box::leak(Box::new(table)));
+            }
+            _ => fail(&format!("unknown option {}", option)),
+        }
+    }
+
+    let status = status.unwrap_or_else(|| rules.initial_board()[..rules.hole_number()].to_vec());
+    if status.len() != rules.hole_number() {
+        fail(&format!("{} needs a status of {} holes", rules, rules.hole_number()));
+    }
+    let mut game_situation = GameSituation::from_with_rules(rules, Player::from_flag(flag), &status);
+    let mut search = Search::new();
+    search.resize_table(tt_bits);
+    search.endgame = endgame;
+
+    let start = Instant::now();
+    let solution = solve(&mut search, &mut game_situation, max_nodes);
+    let elapsed = start.elapsed();
+    match solution {
+        Some(solution) => {
+            let best_move = solution.best_move.map_or("none".to_string(), |mv| mv.code().to_string());
+            println!("value {:+}, best move {}", solution.value, best_move);
+        }
+        None => println!("unsolved"),
+    }
+    eprintln!("{} nodes in {:.1?}", search.stats.nodes, elapsed);
+}
+
+fn parse<T: FromStr>(option: &str, value: &str) -> T {
+    value.parse().unwrap_or_else(|_| fail(&format!("invalid value for {}: {}", option, value)))
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("mancala-solve: {}", message);
+    exit(2);
+}
@@ -0,0 +1,125 @@
+//! Tunes the evaluation weights of `mancala_operator` by self-play.
+//!
+//! ```text
+//! cargo run --release -p mancala-tools --bin mancala-tune -- [--games 400] [--depth 4]
+//!     [--plies 4] [--rounds 2] [--step 0.01] [--seed 1] [--out PATH] [--force]
+//! ```
+//!
+//! Every round plays `--games` games of the current weights against
+//! themselves from random openings of `--plies` moves, then Texel-tunes the
+//! weights on the positions met. The tuned weights are matched against the
+//! embedded ones and, if they win more seeds than they lose or `--force` is
+//! given, written to `--out`, by default the file `t3_2_rust` embeds at build
+//! time.
+
+use std::fmt::Write as _;
+use std::process::exit;
+use std::str::FromStr;
+
+use mancala_tools::selfplay::{play, random_opening, run_match, OperatorConfig};
+use mancala_tools::texel::{error, fit_scale, samples, tune};
+use t3_2_rust::eval::{DEFAULT_WEIGHTS, FEATURE_NAMES, FEATURE_NUMBER};
+use t3_2_rust::rng::Rng;
+
+const WEIGHTS_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../T3/t3_2_rust/src/weights.in");
+
+struct Options {
+    games: usize,
+    depth: i32,
+    plies: usize,
+    rounds: usize,
+    step: f32,
+    seed: u64,
+    out: String,
+    force: bool,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        games: 400,
+        depth: 4,
+        plies: 4,
+        rounds: 2,
+        step: 0.01,
+        seed: 1,
+        out: WEIGHTS_FILE.to_string(),
+        force: false,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        if flag == "--force" {
+            options.force = true;
+            continue;
+        }
+        let value = args.next().ok_or_else(|| format!("{} needs a value", flag))?;
+        match flag.as_str() {
+            "--games" => options.games = parse(&flag, &value)?,
+            "--depth" => options.depth = parse(&flag, &value)?,
+            "--plies" => options.plies = parse(&flag, &value)?,
+            "--rounds" => options.rounds = parse(&flag, &value)?,
+            "--step" => options.step = parse(&flag, &value)?,
+            "--seed" => options.seed = parse(&flag, &value)?,
+            "--out" => options.out = value,
+            _ => return Err(format!("unknown option {}", flag)),
+        }
+    }
+    Ok(options)
+}
+
+fn parse<T: FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid value for {}: {}", option, value))
+}
+
+fn main() {
+    let options = parse_options().unwrap_or_else(|message| {
+        eprintln!("mancala-tune: {}", message);
+        exit(2);
+    });
+    let mut rng = Rng::new(options.seed);
+    let embedded = OperatorConfig { weights: DEFAULT_WEIGHTS, depth: options.depth };
+
+    let mut weights = DEFAULT_WEIGHTS;
+    for round in 1..=options.rounds {
+        let config = OperatorConfig { weights, depth: options.depth };
+        let games = (0..options.games)
+            .map(|_| play(&random_opening(&mut rng, options.plies), &config, &config))
+            .collect::<Vec<_>>();
+        let samples = samples(&games);
+        let scale = fit_scale(&samples, &weights);
+        let before = error(&samples, &weights, scale);
+        weights = tune(&samples, weights, scale, options.step);
+        eprintln!(
+            "round {}: {} positions, scale {:.4}, error {:.6} -> {:.6}, weights {:?}",
+            round,
+            samples.len(),
+            scale,
+            before,
+            error(&samples, &weights, scale),
+            weights,
+        );
+    }
+
+    let openings = (0..options.games / 2).map(|_| random_opening(&mut rng, options.plies)).collect::<Vec<_>>();
+    let tuned = OperatorConfig { weights, depth: options.depth };
+    let (net, [wins, draws, losses]) = run_match(&tuned, &embedded, &openings);
+    eprintln!("tuned against embedded: net {:+} seeds, {} wins, {} draws, {} losses", net, wins, draws, losses);
+    if net <= 0 && !options.force {
+        eprintln!("kept {}, as the tuned weights did no better; --force writes them anyway", options.out);
+        exit(1);
+    }
+
+    if let Err(error) = std::fs::write(&options.out, weights_file(&weights)) {
+        eprintln!("mancala-tune: cannot write {}: {}", options.out, error);
+        exit(1);
+    }
+    eprintln!("wrote {}", options.out);
+}
+
+// a Rust array expression for `include!`
+fn weights_file(weights: &[f32; FEATURE_NUMBER]) -> String {
+    let mut file = String::from("// written by `mancala-tune` in /mancala-tools\n");
+    writeln!(file, "// {}", FEATURE_NAMES.join(", ")).unwrap();
+    let weights = weights.iter().map(|weight| format!("{:.3}", weight)).collect::<Vec<_>>();
+    writeln!(file, "[{}]", weights.join(", ")).unwrap();
+    file
+}
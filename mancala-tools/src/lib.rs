@@ -0,0 +1,13 @@
+//! Native tools around the `t3_2_rust` operator: self-play, the tuning of
+//! its evaluation and matches between engines, built in, compiled to wasm or
+//! speaking a line protocol, from opening suites and rated by Elo. The
+//! binaries live under `src/bin`.
+
+pub mod arena;
+pub mod protocol;
+pub mod rating;
+pub mod selfplay;
+pub mod suite;
+pub mod texel;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,269 @@
+//! A line protocol for engines running as their own processes, after UCI.
+//! The arena writes commands to the standard input of an engine and reads
+//! its answers from the standard output:
+//!
+//! ```text
+//! > mancala
+//! < id name mancala-engine
+//! < mancalaok
+//! > isready
+//! < readyok
+//! > position startpos moves 13 21
+//! > position status 2 4 4 4 0 5 5 1 5 4 4 4 4 4 0
+//! > go movetime 300
+//! < info seldepth 14 nodes 52011 time 297
+//! < bestmove 23
+//! > quit
+//! ```
+//!
+//! `position status` gives the player to move and the 14 holes, as
+//! `mancala_operator` takes them. Engines ignore lines they don't know, and
+//! may send `info` lines at any time. The arena sends `isready` before every
+//! position and skips whatever comes before `readyok`.
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command as Process, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use mancala_core::{GameSituation, Move, Player, HOLE_NUMBER};
+
+use crate::arena::Engine;
+
+// how long an engine may take beyond its move time, and to start up
+const GRACE: Duration = Duration::from_secs(5);
+// how long an engine may take to exit after `quit` before it is killed
+const QUIT_GRACE: Duration = Duration::from_secs(1);
+
+/// What the arena tells an engine.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    /// Asks for the name of the engine, answered by `mancalaok`.
+    Mancala,
+    IsReady,
+    Position(GameSituation),
+    /// Decide in about `movetime` milliseconds.
+    Go { movetime: Option<u32> },
+    Quit,
+}
+
+/// The command of `line`, `None` if it is none the protocol knows.
+pub fn parse_command(line: &str) -> Result<Option<Command>, String> {
+    let mut words = line.split_whitespace();
+    let command = match words.next() {
+        Some("mancala") => Command::Mancala,
+        Some("isready") => Command::IsReady,
+        Some("quit") => Command::Quit,
+        Some("go") => {
+            let mut movetime = None;
+            while let Some(word) = words.next() {
+                if word == "movetime" {
+                    let value = words.next().ok_or("movetime needs a value")?;
+                    movetime = Some(value.parse().map_err(|_| format!("invalid movetime {}", value))?);
+                }
+            }
+            Command::Go { movetime }
+        }
+        Some("position") => {
+            let numbers = |words: &mut dyn Iterator<Item = &str>| {
+                words
+                    .map(|word| word.parse::<i32>().map_err(|_| format!("`{}` is not a number", word)))
+                    .collect::<Result<Vec<_>, _>>()
+            };
+            match words.next() {
+                Some("startpos") => {
+                    let mut game_situation = GameSituation::new(Player::One);
+                    if words.next() == Some("moves") {
+                        for (i, code) in numbers(&mut words)?.into_iter().enumerate() {
+                            if i == 0 {
+                                game_situation = GameSituation::new(Player::from_flag(code / 10));
+                            }
+                            let mv = Move::try_from(code).map_err(|error| format!("{}: {}", code, error))?;
+                            game_situation.act(mv).map_err(|error| format!("{}: {}", code, error))?;
+                        }
+                    }
+                    Command::Position(game_situation)
+                }
+                Some("status") => {
+                    let numbers = numbers(&mut words)?;
+                    if numbers.len() != HOLE_NUMBER + 1 || !matches!(numbers[0], 1 | 2) {
+                        return Err("position status needs a player and 14 holes".to_string());
+                    }
+                    Command::Position(GameSituation::from(Player::from_flag(numbers[0]), &numbers[1..]))
+                }
+                _ => return Err("position needs startpos or status".to_string()),
+            }
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(command))
+}
+
+/// An executable speaking the protocol, as an arena engine.
+pub struct ProcessEngine {
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    movetime: u32,
+}
+
+impl ProcessEngine {
+    /// Starts `command_line`, split at whitespace, and waits for it to name
+    /// itself. Every decision gets `movetime` milliseconds.
+    pub fn spawn(command_line: &str, movetime: u32) -> Result<ProcessEngine, String> {
+        let mut words = command_line.split_whitespace();
+        let program = words.next().ok_or("no command to run")?;
+        let mut child = Process::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|error| format!("cannot run {}: {}", program, error))?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+
+        // lines arrive on another thread, so that a silent engine can time out
+        let (sender, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in stdout.lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = ProcessEngine { name: command_line.to_string(), child, stdin, lines, movetime };
+        engine.send("mancala")?;
+        let deadline = Instant::now() + GRACE;
+        loop {
+            let line = engine.receive(deadline)?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = name.trim().to_string();
+            } else if line.trim() == "mancalaok" {
+                return Ok(engine);
+            }
+        }
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), String> {
+        writeln!(self.stdin, "{}", line)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|error| format!("{} stopped listening: {}", self.name, error))
+    }
+
+    // the next line, unless `deadline` passes first
+    fn receive(&mut self, deadline: Instant) -> Result<String, String> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        self.lines.recv_timeout(timeout).map_err(|error| match error {
+            RecvTimeoutError::Timeout => format!("{} did not answer in time", self.name),
+            RecvTimeoutError::Disconnected => format!("{} has exited", self.name),
+        })
+    }
+}
+
+impl Engine for ProcessEngine {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn decide(&mut self, flag: i32, status: &[i32]) -> Result<i32, String> {
+        // skip what is left of earlier answers, e.g. a `bestmove` too late
+        // for its position
+        self.send("isready")?;
+        let deadline = Instant::now() + GRACE;
+        while self.receive(deadline)?.trim() != "readyok" {}
+
+        let status = status.iter().map(|seeds| seeds.to_string()).collect::<Vec<_>>();
+        self.send(&format!("position status {} {}", flag, status.join(" ")))?;
+        self.send(&format!("go movetime {}", self.movetime))?;
+        let deadline = Instant::now() + Duration::from_millis(self.movetime as u64) + GRACE;
+        loop {
+            let line = self.receive(deadline)?;
+            if let Some(code) = line.strip_prefix("bestmove ") {
+                return code.trim().parse().map_err(|_| format!("{} answered `{}`", self.name, line));
+            }
+        }
+    }
+}
+
+impl Drop for ProcessEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let deadline = Instant::now() + QUIT_GRACE;
+        while Instant::now() < deadline {
+            if !matches!(self.child.try_wait(), Ok(None)) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(parse_command("mancala"), Ok(Some(Command::Mancala)));
+        assert_eq!(parse_command("go movetime 250"), Ok(Some(Command::Go { movetime: Some(250) })));
+        assert_eq!(parse_command("go"), Ok(Some(Command::Go { movetime: None })));
+        assert_eq!(parse_command("ucinewgame"), Ok(None));
+
+        let mut game_situation = GameSituation::new(Player::One);
+        game_situation.act(Move::new(Player::One, 3)).unwrap();
+        game_situation.act(Move::new(Player::One, 1)).unwrap();
+        assert_eq!(parse_command("position startpos moves 13 11"), Ok(Some(Command::Position(game_situation.clone()))));
+
+        let status = game_situation.board().iter().map(|seeds| seeds.to_string()).collect::<Vec<_>>();
+        let Ok(Some(Command::Position(position))) = parse_command(&format!("position status 2 {}", status.join(" "))) else {
+            panic!("expected a position");
+        };
+        assert_eq!((position.actor(), position.board()), (Player::Two, game_situation.board()));
+
+        assert!(parse_command("position startpos moves 11 12").is_err());
+        assert!(parse_command("position status 1 4 4").is_err());
+        assert!(parse_command("go movetime soon").is_err());
+    }
+
+    // answers every `go` twice, or with `info` lines only
+    #[cfg(unix)]
+    const FAKE_ENGINE: &str = r#"
+        while read -r command rest; do
+            case $command in
+                mancala) echo mancalaok ;;
+                isready) echo readyok ;;
+                go) if [ "$1" = chatty ]; then
+                        while true; do echo info nodes 1; sleep 0.01; done
+                    fi
+                    echo bestmove 13; echo bestmove 99 ;;
+            esac
+        done
+    "#;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_engine() {
+        let script = std::env::temp_dir().join(format!("mancala-fake-engine-{}.sh", std::process::id()));
+        std::fs::write(&script, FAKE_ENGINE).unwrap();
+        let command_line = |mode: &str| format!("sh {} {}", script.display(), mode);
+
+        // the extra answer to the first position is not taken for the second
+        let mut engine = ProcessEngine::spawn(&command_line("twice"), 10).unwrap();
+        assert_eq!(engine.decide(1, &[4; 14]), Ok(13));
+        assert_eq!(engine.decide(1, &[4; 14]), Ok(13));
+        drop(engine);
+
+        // `info` lines don't put off the deadline, and an engine that
+        // ignores `quit` is killed
+        let mut engine = ProcessEngine::spawn(&command_line("chatty"), 10).unwrap();
+        let start = Instant::now();
+        assert!(engine.decide(1, &[4; 14]).unwrap_err().contains("did not answer in time"));
+        drop(engine);
+        assert!(start.elapsed() < GRACE + QUIT_GRACE + Duration::from_secs(2));
+        std::fs::remove_file(script).unwrap();
+    }
+}
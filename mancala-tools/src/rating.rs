@@ -0,0 +1,166 @@
+//! Elo differences from match results, and the sequential probability ratio
+//! test that decides whether a patch gained strength with as few games as
+//! the results allow.
+
+use std::fmt;
+
+// the normal quantile of a two-sided 95% interval
+const Z_95: f64 = 1.959_964;
+
+/// The Elo difference that expects `score` points per game, between 0 and 1.
+pub fn elo(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// The points per game expected at the Elo difference `elo`.
+pub fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// An Elo difference with its 95% confidence interval.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    pub elo: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:+.1} [{:+.1}, {:+.1}]", self.elo, self.low, self.high)
+    }
+}
+
+/// The logistic Elo difference of wins, draws and losses, `None` without
+/// games. Scores of 0 or 1 give infinite bounds.
+pub fn estimate([wins, draws, losses]: [u32; 3]) -> Option<Estimate> {
+    let games = (wins + draws + losses) as f64;
+    if games == 0.0 {
+        return None;
+    }
+    let (win, draw, loss) = (wins as f64 / games, draws as f64 / games, losses as f64 / games);
+    let score = win + draw / 2.0;
+    let variance = win * (1.0 - score).powi(2) + draw * (0.5 - score).powi(2) + loss * score.powi(2);
+    let margin = Z_95 * (variance / games).sqrt();
+    Some(Estimate {
+        elo: elo(score),
+        low: elo((score - margin).max(0.0)),
+        high: elo((score + margin).min(1.0)),
+    })
+}
+
+/// The BayesElo model of results: draws come from two engines within
+/// `draw_elo` of each other, so that a win of the stronger by `elo` has
+/// probability `expected_score(elo - draw_elo)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BayesElo {
+    pub elo: f64,
+    pub draw_elo: f64,
+}
+
+impl BayesElo {
+    /// The model that expects exactly the shares of wins and losses seen,
+    /// `None` unless there are both.
+    pub fn fit([wins, draws, losses]: [u32; 3]) -> Option<BayesElo> {
+        if wins == 0 || losses == 0 {
+            return None;
+        }
+        let games = (wins + draws + losses) as f64;
+        let (win, loss) = (wins as f64 / games, losses as f64 / games);
+        Some(BayesElo {
+            elo: 200.0 * (win / loss * (1.0 - loss) / (1.0 - win)).log10(),
+            draw_elo: 200.0 * ((1.0 - loss) / loss * (1.0 - win) / win).log10(),
+        })
+    }
+
+    /// The probabilities of a win, a draw and a loss.
+    pub fn probabilities(&self) -> [f64; 3] {
+        let win = expected_score(self.elo - self.draw_elo);
+        let loss = expected_score(-self.elo - self.draw_elo);
+        [win, 1.0 - win - loss, loss]
+    }
+}
+
+/// What the SPRT makes of the games so far.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// The engine is `elo1` stronger rather than `elo0`.
+    Accept,
+    /// The engine is `elo0` stronger rather than `elo1`.
+    Reject,
+    /// More games are needed.
+    Continue,
+}
+
+/// A sequential probability ratio test of the BayesElo difference `elo0`
+/// against `elo1`, wrongly accepting with probability `alpha` and wrongly
+/// rejecting with probability `beta`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64) -> Sprt {
+        Sprt { elo0, elo1, alpha: 0.05, beta: 0.05 }
+    }
+
+    /// The bounds the log-likelihood ratio has to leave for a verdict.
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    /// The log-likelihood ratio of `elo1` against `elo0` given the results,
+    /// with the draw Elo fitted to them; 0 until there are wins and losses.
+    pub fn llr(&self, results: [u32; 3]) -> f64 {
+        let Some(fitted) = BayesElo::fit(results) else {
+            return 0.0;
+        };
+        let h0 = BayesElo { elo: self.elo0, ..fitted }.probabilities();
+        let h1 = BayesElo { elo: self.elo1, ..fitted }.probabilities();
+        (0..3).map(|i| results[i] as f64 * (h1[i] / h0[i]).ln()).sum()
+    }
+
+    pub fn verdict(&self, results: [u32; 3]) -> Verdict {
+        let llr = self.llr(results);
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            Verdict::Accept
+        } else if llr <= lower {
+            Verdict::Reject
+        } else {
+            Verdict::Continue
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rating() {
+        assert_eq!(elo(0.5), 0.0);
+        assert!((elo(0.75) - 190.85).abs() < 0.01);
+        assert!((expected_score(elo(0.3)) - 0.3).abs() < 1e-9);
+
+        let estimate = estimate([60, 20, 20]).unwrap();
+        assert!(estimate.low < estimate.elo && estimate.elo < estimate.high);
+        assert!((estimate.elo - elo(0.7)).abs() < 1e-9);
+        assert_eq!(super::estimate([0; 3]), None);
+
+        // the fitted model gives back the shares it was fitted to
+        let bayes_elo = BayesElo::fit([60, 20, 20]).unwrap();
+        let [win, draw, loss] = bayes_elo.probabilities();
+        assert!((win - 0.6).abs() < 1e-9 && (draw - 0.2).abs() < 1e-9 && (loss - 0.2).abs() < 1e-9);
+        assert_eq!(BayesElo::fit([10, 5, 0]), None);
+
+        let sprt = Sprt::new(0.0, 10.0);
+        assert_eq!(sprt.verdict([300, 100, 100]), Verdict::Accept);
+        assert_eq!(sprt.verdict([100, 100, 300]), Verdict::Reject);
+        assert_eq!(sprt.verdict([11, 2, 10]), Verdict::Continue);
+    }
+}
@@ -0,0 +1,108 @@
+use mancala_core::{GameSituation, Move, Player};
+use t3_2_rust::eval::{FeatureEvaluator, FEATURE_NUMBER};
+use t3_2_rust::rng::Rng;
+use t3_2_rust::search::{ordered_moves, Search};
+
+/// How `mancala_operator` plays: the weights of its evaluation and how many
+/// turns it looks ahead.
+#[derive(Clone, Copy, Debug)]
+pub struct OperatorConfig {
+    pub weights: [f32; FEATURE_NUMBER],
+    pub depth: i32,
+}
+
+impl OperatorConfig {
+    pub fn choose(&self, game_situation: &mut GameSituation) -> Move {
+        let mut search = Search::with_evaluator(FeatureEvaluator::new(self.weights));
+        let (mv, _) = search.best_move(game_situation, self.depth).expect("the game has not ended");
+        mv
+    }
+}
+
+/// A finished game and every position met before its end.
+#[derive(Clone, Debug)]
+pub struct Game {
+    pub positions: Vec<GameSituation>,
+    pub last: GameSituation,
+}
+
+impl Game {
+    /// Player one's final score minus player two's.
+    pub fn score_difference(&self) -> i32 {
+        self.last.score(Player::One) - self.last.score(Player::Two)
+    }
+}
+
+/// The standard board after `plies` random moves, none of which ends the
+/// game.
+pub fn random_opening(rng: &mut Rng, plies: usize) -> GameSituation {
+    loop {
+        let mut game_situation = GameSituation::new(Player::One);
+        for _ in 0..plies {
+            let moves = ordered_moves(&game_situation, None).collect::<Vec<_>>();
+            if moves.is_empty() {
+                break;
+            }
+            game_situation.act(moves[rng.below(moves.len())]).unwrap();
+        }
+        if !game_situation.ended() {
+            return game_situation;
+        }
+    }
+}
+
+/// Plays `opening` to the end, `one` moving for player one and `two` for
+/// player two.
+pub fn play(opening: &GameSituation, one: &OperatorConfig, two: &OperatorConfig) -> Game {
+    let mut game_situation = opening.clone();
+    let mut positions = Vec::new();
+    while !game_situation.ended() {
+        positions.push(game_situation.clone());
+        let config = if game_situation.actor() == Player::One { one } else { two };
+        let mv = config.choose(&mut game_situation);
+        game_situation.act(mv).unwrap();
+    }
+    Game { positions, last: game_situation }
+}
+
+/// Net seeds, wins, draws and losses of `config` against `opponent` over
+/// `openings`, each played with both colours.
+pub fn run_match(config: &OperatorConfig, opponent: &OperatorConfig, openings: &[GameSituation]) -> (i32, [u32; 3]) {
+    let mut net = 0;
+    let mut results = [0; 3];
+    for opening in openings {
+        for config_moves_first in [true, false] {
+            let difference = if config_moves_first {
+                play(opening, config, opponent).score_difference()
+            } else {
+                -play(opening, opponent, config).score_difference()
+            };
+            net += difference;
+            results[if difference > 0 { 0 } else if difference == 0 { 1 } else { 2 }] += 1;
+        }
+    }
+    (net, results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use t3_2_rust::eval::DEFAULT_WEIGHTS;
+
+    #[test]
+    fn test_play() {
+        let config = OperatorConfig { weights: DEFAULT_WEIGHTS, depth: 2 };
+        let opening = random_opening(&mut Rng::new(7), 4);
+        assert_eq!(opening.history().count(), 4);
+
+        let game = play(&opening, &config, &config);
+        assert!(game.last.ended());
+        assert_eq!(game.positions[0], opening);
+        assert_eq!(game.last.score(Player::One) + game.last.score(Player::Two), 48);
+
+        // a configuration against itself nets nothing over both colours
+        let (net, results) = run_match(&config, &config, &[opening]);
+        assert_eq!(net, 0);
+        assert_eq!(results.iter().sum::<u32>(), 2);
+    }
+}
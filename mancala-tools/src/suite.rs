@@ -0,0 +1,138 @@
+//! Opening suites: positions a few moves into the game, balanced by the
+//! search, that engines play from with both colours. A suite file holds one
+//! opening per line as the move codes leading to it from the standard start,
+//! or `startpos` for the start itself; `#` starts a comment.
+
+use std::collections::HashSet;
+
+use mancala_core::{GameSituation, Move, Player};
+use t3_2_rust::eval::FeatureEvaluator;
+use t3_2_rust::opening_book;
+use t3_2_rust::rng::Rng;
+use t3_2_rust::search::{ordered_moves, Search};
+
+// openings tried per opening kept before giving up
+const ATTEMPTS: usize = 1000;
+
+// the line of an opening without moves, which would otherwise be blank
+const START_POSITION: &str = "startpos";
+
+#[derive(Clone, Copy, Debug)]
+pub struct SuiteConfig {
+    /// Openings wanted.
+    pub count: usize,
+    /// Random moves from the start.
+    pub random_plies: usize,
+    /// Moves of the opening book after the random ones, as long as it knows
+    /// the position.
+    pub book_plies: usize,
+    /// Turns the search looks ahead to value an opening.
+    pub depth: i32,
+    /// The most seeds an opening may be worth to either player.
+    pub margin: f32,
+}
+
+/// Up to `config.count` different openings of player one moving first whose
+/// value is within `config.margin` of even.
+pub fn generate(config: &SuiteConfig, rng: &mut Rng) -> Vec<GameSituation> {
+    let mut search = Search::with_evaluator(FeatureEvaluator::default());
+    let mut seen = HashSet::new();
+    let mut openings = Vec::new();
+    for _ in 0..config.count * ATTEMPTS {
+        if openings.len() == config.count {
+            break;
+        }
+        let mut game_situation = GameSituation::new(Player::One);
+        for _ in 0..config.random_plies {
+            let moves = ordered_moves(&game_situation, None).collect::<Vec<_>>();
+            if moves.is_empty() {
+                break;
+            }
+            game_situation.act(moves[rng.below(moves.len())]).unwrap();
+        }
+        for _ in 0..config.book_plies {
+            match opening_book().lookup(&game_situation) {
+                Some(mv) => game_situation.act(mv).unwrap(),
+                None => break,
+            };
+        }
+        if game_situation.ended() || !seen.insert((game_situation.actor(), game_situation.board().to_vec())) {
+            continue;
+        }
+        let (_, value) = search.best_move(&mut game_situation, config.depth).unwrap();
+        if value.abs() <= config.margin {
+            openings.push(game_situation);
+        }
+    }
+    openings
+}
+
+/// The suite file of `openings`, which must come from the standard start.
+pub fn format_suite(openings: &[GameSituation]) -> String {
+    let mut text = String::new();
+    for opening in openings {
+        let moves = opening.history().map(|mv| mv.code().to_string()).collect::<Vec<_>>();
+        if moves.is_empty() {
+            text.push_str(START_POSITION);
+        } else {
+            text.push_str(&moves.join(" "));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// The openings of a suite file, each replayed from the standard start.
+pub fn parse_suite(text: &str) -> Result<Vec<GameSituation>, String> {
+    let mut openings = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == START_POSITION {
+            openings.push(GameSituation::new(Player::One));
+            continue;
+        }
+        let invalid = |reason: String| format!("line {}: {}", number + 1, reason);
+        let moves = line
+            .split_whitespace()
+            .map(|code| code.parse::<i32>().map_err(|_| invalid(format!("`{}` is not a move", code))))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut game_situation = GameSituation::new(Player::from_flag(moves[0] / 10));
+        for code in moves {
+            let mv = Move::try_from(code).map_err(|error| invalid(format!("{}: {}", code, error)))?;
+            game_situation.act(mv).map_err(|error| invalid(format!("{}: {}", code, error)))?;
+        }
+        if game_situation.ended() {
+            return Err(invalid("the game has ended".to_string()));
+        }
+        openings.push(game_situation);
+    }
+    Ok(openings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suite() {
+        let config = SuiteConfig { count: 5, random_plies: 3, book_plies: 1, depth: 2, margin: 3.0 };
+        let openings = generate(&config, &mut Rng::new(1));
+        assert_eq!(openings.len(), 5);
+        assert_eq!(parse_suite(&format_suite(&openings)).unwrap(), openings);
+
+        // the start has no moves, yet its line is not blank
+        let config = SuiteConfig { count: 1, random_plies: 0, book_plies: 0, depth: 2, margin: 3.0 };
+        let openings = generate(&config, &mut Rng::new(1));
+        assert_eq!(openings, [GameSituation::new(Player::One)]);
+        let text = format_suite(&[openings[0].clone(), openings[0].clone()]);
+        assert_eq!(text, "startpos\nstartpos\n");
+        assert_eq!(parse_suite(&text).unwrap().len(), 2);
+
+        assert_eq!(parse_suite("# none yet\n\n").unwrap(), []);
+        assert!(parse_suite("11 12").is_err());
+        assert!(parse_suite("13 x").is_err());
+    }
+}
@@ -0,0 +1,109 @@
+//! Texel tuning: fit the weights of `FeatureEvaluator` so that a sigmoid of
+//! the evaluation predicts the results of recorded games.
+
+use t3_2_rust::eval::{features, FEATURE_NUMBER};
+
+use crate::selfplay::Game;
+
+// rounds of `tune` over every weight, in case the error keeps creeping down
+const MAX_PASSES: usize = 1000;
+
+/// A recorded position: its features, and 1, 0.5 or 0 as player one went on
+/// to win, draw or lose.
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    pub features: [f32; FEATURE_NUMBER],
+    pub result: f64,
+}
+
+pub fn samples(games: &[Game]) -> Vec<Sample> {
+    let mut samples = Vec::new();
+    for game in games {
+        let result = match game.score_difference() {
+            difference if difference > 0 => 1.0,
+            0 => 0.5,
+            _ => 0.0,
+        };
+        for position in &game.positions {
+            samples.push(Sample { features: features(position), result });
+        }
+    }
+    samples
+}
+
+/// Mean squared error of `sigmoid(scale * evaluation)` against the results.
+pub fn error(samples: &[Sample], weights: &[f32; FEATURE_NUMBER], scale: f64) -> f64 {
+    let total = samples
+        .iter()
+        .map(|sample| {
+            let value = sample.features.iter().zip(weights).map(|(f, w)| (f * w) as f64).sum::<f64>();
+            let predicted = 1.0 / (1.0 + (-scale * value).exp());
+            (predicted - sample.result).powi(2)
+        })
+        .sum::<f64>();
+    total / samples.len().max(1) as f64
+}
+
+/// The scale that makes `weights` fit the results best, by ternary search.
+pub fn fit_scale(samples: &[Sample], weights: &[f32; FEATURE_NUMBER]) -> f64 {
+    let (mut low, mut high) = (0.0, 4.0);
+    for _ in 0..100 {
+        let a = low + (high - low) / 3.0;
+        let b = high - (high - low) / 3.0;
+        if error(samples, weights, a) < error(samples, weights, b) {
+            high = b;
+        } else {
+            low = a;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// Moves one weight at a time by `step` while that lowers the error, keeping
+/// the store weight, which `scale` stands in for, as it is.
+pub fn tune(samples: &[Sample], weights: [f32; FEATURE_NUMBER], scale: f64, step: f32) -> [f32; FEATURE_NUMBER] {
+    let mut weights = weights;
+    let mut best = error(samples, &weights, scale);
+    for _ in 0..MAX_PASSES {
+        let mut improved = false;
+        for i in 1..FEATURE_NUMBER {
+            for delta in [step, -step] {
+                let mut trial = weights;
+                trial[i] += delta;
+                let trial_error = error(samples, &trial, scale);
+                if trial_error < best {
+                    best = trial_error;
+                    weights = trial;
+                    improved = true;
+                    break;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    weights
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tune() {
+        // player one wins exactly when it has more material
+        let samples = (-5..=5)
+            .map(|material| Sample {
+                features: [0.0, material as f32, 0.0, 0.0, 0.0, 0.0],
+                result: if material > 0 { 1.0 } else if material == 0 { 0.5 } else { 0.0 },
+            })
+            .collect::<Vec<_>>();
+        let weights = [1.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        let scale = fit_scale(&samples, &weights);
+        let tuned = tune(&samples, weights, scale, 0.1);
+        assert!(tuned[1] > 0.5);
+        assert_eq!(tuned[2..], [0.0; 4]);
+        assert!(error(&samples, &tuned, scale) < error(&samples, &weights, scale));
+    }
+}
@@ -0,0 +1,247 @@
+//! Engines built by `wasm-pack`, loaded from their `*_bg.wasm` the way
+//! `T3/test.js` imports them from `pkg`, and run by wasmtime without Node.js.
+//! Only `mancala_operator(flag, status)` and the few imports a decision may
+//! need are supported: the clock, throwing and logging. Calling any other
+//! import stops the decision with an error naming it.
+
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use wasmtime::{Caller, Config, Engine as Runtime, Extern, Func, FuncType, Linker, Memory, Module, Store, TypedFunc, Val, ValType};
+
+use crate::arena::Engine;
+
+/// The longest a decision may take in the course battles.
+pub const TIME_LIMIT: Duration = Duration::from_millis(2000);
+
+// how often running modules look at the clock
+const TICK: Duration = Duration::from_millis(10);
+
+// what the arena does when a module calls one of its imports
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Import {
+    /// `Date.now()` or `performance.now()`, in milliseconds.
+    Clock,
+    /// `__wbindgen_throw(pointer, length)`, which stops the call.
+    Throw,
+    /// Logging, dropping JS objects and the like, which need no answer.
+    Ignore,
+    Unsupported,
+}
+
+impl Import {
+    fn of(field: &str, ty: &FuncType) -> Import {
+        let returns_f64 = ty.results().len() == 1 && matches!(ty.results().next(), Some(ValType::F64));
+        if field.starts_with("__wbg_now_") && ty.params().len() == 0 && returns_f64 {
+            Import::Clock
+        } else if field == "__wbindgen_throw" {
+            Import::Throw
+        } else if (field.starts_with("__wbg_log_") ||
+            field == "__wbindgen_object_drop_ref" ||
+            field == "__wbindgen_init_externref_table") && ty.results().len() == 0 {
+            Import::Ignore
+        } else {
+            Import::Unsupported
+        }
+    }
+}
+
+// the UTF-8 string of `length` bytes at `pointer` in the memory of the caller
+fn read_string(caller: &mut Caller<'_, ()>, pointer: i32, length: i32) -> Option<String> {
+    let Some(Extern::Memory(memory)) = caller.get_export("memory") else {
+        return None;
+    };
+    let mut bytes = vec![0; length.max(0) as usize];
+    memory.read(&*caller, pointer as usize, &mut bytes).ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+// one runtime for every module, its epoch advanced by a thread every tick
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        let mut config = Config::new();
+        config.epoch_interruption(true);
+        let runtime = Runtime::new(&config).expect("the configuration is supported");
+        let ticking = runtime.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(TICK);
+            ticking.increment_epoch();
+        });
+        runtime
+    })
+}
+
+/// A wasm-bindgen module exporting `mancala_operator`.
+pub struct WasmEngine {
+    name: String,
+    store: Store<()>,
+    memory: Memory,
+    malloc: Func,
+    operator: TypedFunc<(i32, i32, i32), i32>,
+    time_limit: Duration,
+}
+
+impl WasmEngine {
+    /// Loads the module at `path`, named after its file.
+    pub fn load(path: &str) -> Result<WasmEngine, String> {
+        let bytes = std::fs::read(path).map_err(|error| format!("cannot read {}: {}", path, error))?;
+        let name = Path::new(path).file_name().map_or(path.into(), |name| name.to_string_lossy().into_owned());
+        WasmEngine::new(name, &bytes, TIME_LIMIT)
+    }
+
+    /// Instantiates a module from its binary or text, stopping every decision
+    /// after `time_limit`.
+    pub fn new(name: String, bytes: &[u8], time_limit: Duration) -> Result<WasmEngine, String> {
+        let module = Module::new(runtime(), bytes).map_err(|error| format!("{}: {}", name, error))?;
+        let mut store = Store::new(runtime(), ());
+        store.set_epoch_deadline(u64::MAX / 2);
+
+        let mut linker = Linker::new(runtime());
+        for import in module.imports() {
+            let (module_name, field) = (import.module().to_string(), import.name().to_string());
+            let Some(ty) = import.ty().func().cloned() else {
+                return Err(format!("{} imports {}.{}, which is not a function", name, module_name, field));
+            };
+            let kind = Import::of(&field, &ty);
+            let stub = Func::new(&mut store, ty.clone(), move |mut caller, params, results| match kind {
+                Import::Clock => {
+                    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+                    results[0] = Val::F64((now.as_secs_f64() * 1000.0).to_bits());
+                    Ok(())
+                }
+                Import::Throw => {
+                    let message = match params {
+                        [Val::I32(pointer), Val::I32(length)] => read_string(&mut caller, *pointer, *length),
+                        _ => None,
+                    };
+                    Err(wasmtime::Error::msg(format!("threw {}", message.unwrap_or_else(|| "an error".to_string()))))
+                }
+                Import::Ignore => Ok(()),
+                Import::Unsupported => {
+                    Err(wasmtime::Error::msg(format!("called {}.{}, which the arena does not provide", module_name, field)))
+                }
+            });
+            linker.define(&store, import.module(), import.name(), stub).map_err(|error| error.to_string())?;
+        }
+        let instance = linker.instantiate(&mut store, &module).map_err(|error| format!("{}: {}", name, error))?;
+        if let Ok(start) = instance.get_typed_func::<(), ()>(&mut store, "__wbindgen_start") {
+            start.call(&mut store, ()).map_err(|error| format!("{}: {}", name, error))?;
+        }
+
+        let missing = |export: &str| format!("{} does not export {}", name, export);
+        let memory = instance.get_memory(&mut store, "memory").ok_or_else(|| missing("memory"))?;
+        let malloc = instance.get_func(&mut store, "__wbindgen_malloc").ok_or_else(|| missing("__wbindgen_malloc"))?;
+        let operator = instance
+            .get_typed_func(&mut store, "mancala_operator")
+            .map_err(|_| missing("mancala_operator(flag, status)"))?;
+        Ok(WasmEngine { name, store, memory, malloc, operator, time_limit })
+    }
+
+    // copies `status` into the memory of the module as the JS glue does
+    fn pass_array(&mut self, status: &[i32]) -> Result<i32, String> {
+        let size = Val::I32(status.len() as i32 * 4);
+        // newer wasm-bindgen passes the alignment too
+        let params = if self.malloc.ty(&self.store).params().len() == 2 { vec![size, Val::I32(4)] } else { vec![size] };
+        let mut pointer = [Val::I32(0)];
+        self.malloc.call(&mut self.store, &params, &mut pointer).map_err(|error| format!("{}: {}", self.name, error))?;
+        let pointer = pointer[0].unwrap_i32();
+        let bytes = status.iter().flat_map(|seeds| seeds.to_le_bytes()).collect::<Vec<_>>();
+        self.memory.write(&mut self.store, pointer as usize, &bytes).map_err(|error| format!("{}: {}", self.name, error))?;
+        Ok(pointer)
+    }
+}
+
+impl Engine for WasmEngine {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn decide(&mut self, flag: i32, status: &[i32]) -> Result<i32, String> {
+        let pointer = self.pass_array(status)?;
+        let ticks = self.time_limit.as_millis().div_ceil(TICK.as_millis()) as u64;
+        self.store.set_epoch_deadline(ticks + 1);
+        let result = self.operator.call(&mut self.store, (flag, pointer, status.len() as i32));
+        self.store.set_epoch_deadline(u64::MAX / 2);
+        result.map_err(|error| match error.downcast_ref::<wasmtime::Trap>() {
+            Some(wasmtime::Trap::Interrupt) => format!("{} took over {} ms", self.name, self.time_limit.as_millis()),
+            _ => format!("{}: {}", self.name, error.root_cause()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the exports of a wasm-bindgen build, playing the first pit with seeds
+    const FIRST_PIT: &str = r#"(module
+        (import "./first_pit_bg.js" "__wbg_now_3141b3797eb98e0b" (func $now (result f64)))
+        (import "./first_pit_bg.js" "__wbg_log_1d3ae0273d8f4f8a" (func $log (param i32)))
+        (memory (export "memory") 1)
+        (global $next (mut i32) (i32.const 1024))
+        (func (export "__wbindgen_malloc") (param $size i32) (param $align i32) (result i32)
+            (global.get $next)
+            (global.set $next (i32.add (global.get $next) (local.get $size))))
+        (func (export "mancala_operator") (param $flag i32) (param $status i32) (param $len i32) (result i32)
+            (local $pit i32)
+            (drop (call $now))
+            (call $log (local.get $flag))
+            ;; the pits of player two start at hole 7
+            (if (i32.eq (local.get $flag) (i32.const 2))
+                (then (local.set $status (i32.add (local.get $status) (i32.const 28)))))
+            (local.set $pit (i32.const 1))
+            (block $found
+                (loop $next_pit
+                    (br_if $found (i32.load (local.get $status)))
+                    (local.set $status (i32.add (local.get $status) (i32.const 4)))
+                    (local.set $pit (i32.add (local.get $pit) (i32.const 1)))
+                    (br $next_pit)))
+            (i32.add (i32.mul (local.get $flag) (i32.const 10)) (local.get $pit))))"#;
+
+    const SPINNING: &str = r#"(module
+        (memory (export "memory") 1)
+        (func (export "__wbindgen_malloc") (param i32) (result i32) (i32.const 1024))
+        (func (export "mancala_operator") (param i32 i32 i32) (result i32)
+            (loop $forever (br $forever))
+            (i32.const 11)))"#;
+
+    // calls an import that only looks like the clock
+    const KNOWN: &str = r#"(module
+        (import "./known_bg.js" "__wbg_known_8f2d0c5e1a3b7a94" (func $known (result f64)))
+        (memory (export "memory") 1)
+        (func (export "__wbindgen_malloc") (param i32 i32) (result i32) (i32.const 1024))
+        (func (export "mancala_operator") (param i32 i32 i32) (result i32)
+            (drop (call $known))
+            (i32.const 11)))"#;
+
+    const THROWING: &str = r#"(module
+        (import "./throwing_bg.js" "__wbindgen_throw" (func $throw (param i32 i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 16) "no legal move")
+        (func (export "__wbindgen_malloc") (param i32 i32) (result i32) (i32.const 1024))
+        (func (export "mancala_operator") (param i32 i32 i32) (result i32)
+            (call $throw (i32.const 16) (i32.const 13))
+            (i32.const 11)))"#;
+
+    #[test]
+    fn test_wasm_engine() {
+        let mut engine = WasmEngine::new("first_pit".to_string(), FIRST_PIT.as_bytes(), TIME_LIMIT).unwrap();
+        assert_eq!(engine.decide(1, &[0, 0, 3, 4, 4, 4, 1, 4, 4, 4, 4, 4, 4, 0]), Ok(13));
+        assert_eq!(engine.decide(2, &[0, 0, 3, 4, 4, 4, 1, 0, 4, 4, 4, 4, 4, 0]), Ok(22));
+
+        let mut spinning = WasmEngine::new("spinning".to_string(), SPINNING.as_bytes(), Duration::from_millis(50)).unwrap();
+        let error = spinning.decide(1, &[4; 14]).unwrap_err();
+        assert!(error.contains("took over 50 ms"), "{}", error);
+
+        assert!(WasmEngine::new("empty".to_string(), b"(module)", TIME_LIMIT).is_err());
+
+        let mut known = WasmEngine::new("known".to_string(), KNOWN.as_bytes(), TIME_LIMIT).unwrap();
+        let error = known.decide(1, &[4; 14]).unwrap_err();
+        assert!(error.contains("called ./known_bg.js.__wbg_known_8f2d0c5e1a3b7a94"), "{}", error);
+        let mut throwing = WasmEngine::new("throwing".to_string(), THROWING.as_bytes(), TIME_LIMIT).unwrap();
+        let error = throwing.decide(1, &[4; 14]).unwrap_err();
+        assert!(error.contains("threw no legal move"), "{}", error);
+    }
+}